@@ -3,5 +3,5 @@ mod generate_ast;
 use generate_ast::*;
 
 fn main() -> io::Result<()> {
-    generate_ast(&"src")
+    generate_ast("src")
 }
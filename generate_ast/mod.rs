@@ -11,12 +11,13 @@ struct TreeType {
 pub fn generate_ast(output_dir: &str) -> io::Result<()> {
     define_ast(
         output_dir,
-        &"Expr",
+        "Expr",
         &["error", "token", "literal", "rc"],
         &[
             "Assign   : Token name, Box<Expr> value",
             "Binary   : Box<Expr> left, Token operator, Box<Expr> right",
-            "Call     : Rc<Expr> callee, Token paren, Vec<Expr> arguments",
+            "Call     : Rc<Expr> callee, Token paren, Vec<Expr> arguments, bool optional",
+            "Get      : Box<Expr> object, Token name, bool optional",
             "Grouping : Box<Expr> expression",
             "Literal  : Option<Literal> value",
             "Logical  : Box<Expr> left, Token operator, Box<Expr> right",
@@ -26,16 +27,20 @@ pub fn generate_ast(output_dir: &str) -> io::Result<()> {
     )?;
     define_ast(
         output_dir,
-        &"Stmt",
+        "Stmt",
         &["error", "expr", "token"],
         &[
+            "Assert     : Token keyword, Expr condition, Option<Expr> message, String source",
             "Block      : Vec<Stmt> statements",
-            "Break      : Token token",
+            "Break      : Token token, Option<Token> label",
+            "Continue   : Token token, Option<Token> label",
+            "Do         : Box<Stmt> body, Expr condition, Option<Token> label",
+            "Enum       : Token name, Vec<Token> members",
             "Expression : Expr expression",
             "If         : Expr condition, Box<Stmt> then_branch, Option<Box<Stmt>> else_branch",
             "Print      : Expr expression",
-            "Var        : Token name, Option<Expr> initializer",
-            "While      : Expr condition, Box<Stmt> body",
+            "Var        : Token name, Option<Token> type_annotation, Option<Expr> initializer",
+            "While      : Expr condition, Box<Stmt> body, Option<Expr> increment, Option<Token> label",
         ],
     )?;
     Ok(())
@@ -77,7 +82,7 @@ fn define_ast(
 
     writeln!(file, "\npub enum {base_name} {{")?;
     for t in &tree_types {
-        write!(file, "    {}({}),\n", t.base_class_name, t.class_name)?;
+        writeln!(file, "    {}({}),", t.base_class_name, t.class_name)?;
     }
     writeln!(file, "}}\n")?;
 
@@ -98,9 +103,9 @@ fn define_ast(
     writeln!(file, "}}\n")?;
 
     for t in &tree_types {
-        write!(file, "pub struct {} {{\n", t.class_name)?;
+        writeln!(file, "pub struct {} {{", t.class_name)?;
         for f in &t.fields {
-            write!(file, "    pub {},\n", f)?;
+            writeln!(file, "    pub {},", f)?;
         }
         writeln!(file, "}}\n")?;
     }
@@ -118,7 +123,7 @@ fn define_ast(
     writeln!(file, "}}\n")?;
 
     for t in &tree_types {
-        write!(file, "impl {} {{\n", t.class_name)?;
+        writeln!(file, "impl {} {{", t.class_name)?;
         writeln!(
             file,
             "    pub fn accept<T>(&self, visitor: &dyn {}Visitor<T>) -> Result<T, LoxResult> {{",
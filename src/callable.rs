@@ -6,6 +6,7 @@ use std::rc::Rc;
 use crate::error::*;
 use crate::interpreter::*;
 use crate::literal::*;
+use crate::token::*;
 
 #[derive(Clone)]
 pub struct Callable {
@@ -25,12 +26,18 @@ impl PartialEq for Callable {
 }
 
 pub trait LoxCallable {
+    // `paren` is the call's closing parenthesis, where errors are reported
     fn call(
         &self,
         interpreter: &Interpreter,
         arguments: Vec<Literal>,
+        paren: &Token,
     ) -> Result<Literal, LoxResult>;
     fn arity(&self) -> usize;
+    // variadic callables take `arity` or more arguments
+    fn is_variadic(&self) -> bool {
+        false
+    }
 }
 
 impl LoxCallable for Callable {
@@ -38,11 +45,16 @@ impl LoxCallable for Callable {
         &self,
         interpreter: &Interpreter,
         arguments: Vec<Literal>,
+        paren: &Token,
     ) -> Result<Literal, LoxResult> {
-        self.func.call(interpreter, arguments)
+        self.func.call(interpreter, arguments, paren)
     }
 
     fn arity(&self) -> usize {
         self.func.arity()
     }
+
+    fn is_variadic(&self) -> bool {
+        self.func.is_variadic()
+    }
 }
@@ -85,7 +85,7 @@ mod tests {
         let mut e = Environment::new();
         e.define("Three", Literal::String("foo".to_string()));
 
-        let three_tok = Token::new(TokenType::Identifier, "Three".to_string(), None, 0);
+        let three_tok = Token::new(TokenType::Identifier, "Three".to_string(), None, 0, 1);
         assert_eq!(
             e.get(&three_tok).unwrap(),
             Literal::String("foo".to_string())
@@ -95,21 +95,21 @@ mod tests {
     #[test]
     fn error_when_variable_undefined() {
         let e = Environment::new();
-        let three_tok = Token::new(TokenType::Identifier, "Three".to_string(), None, 0);
+        let three_tok = Token::new(TokenType::Identifier, "Three".to_string(), None, 0, 1);
         assert!(e.get(&three_tok).is_err());
     }
     #[test]
     fn error_when_assigning_to_undefined_variable() {
         let mut e = Environment::new();
-        let four_tok = Token::new(TokenType::Identifier, "Four".to_string(), None, 0);
+        let four_tok = Token::new(TokenType::Identifier, "Four".to_string(), None, 0, 1);
         assert!(e.assign(&four_tok, Literal::Nil).is_err());
     }
 
     #[test]
     fn can_reassign_existing_variable() {
         let mut e = Environment::new();
-        let four_tok = Token::new(TokenType::Identifier, "Four".to_string(), None, 0);
-        e.define(&"Four".to_string(), Literal::Number(73.1));
+        let four_tok = Token::new(TokenType::Identifier, "Four".to_string(), None, 0, 1);
+        e.define("Four", Literal::Number(73.1));
         assert!(e.assign(&four_tok, Literal::Number(89.5)).is_ok());
         assert_eq!(e.get(&four_tok).unwrap(), Literal::Number(89.5));
     }
@@ -124,22 +124,20 @@ mod tests {
     #[test]
     fn can_read_from_enclosed_environment() {
         let e = Rc::new(RefCell::new(Environment::new()));
-        e.borrow_mut()
-            .define(&"Five".to_string(), Literal::Number(77.8));
+        e.borrow_mut().define("Five", Literal::Number(77.8));
 
         let f = Environment::new_with_enclosing(Rc::clone(&e));
-        let five_tok = Token::new(TokenType::Identifier, "Five".to_string(), None, 0);
+        let five_tok = Token::new(TokenType::Identifier, "Five".to_string(), None, 0, 1);
         assert_eq!(f.get(&five_tok).unwrap(), Literal::Number(77.8));
     }
 
     #[test]
     fn can_assign_to_enclosed_environment() {
         let e = Rc::new(RefCell::new(Environment::new()));
-        e.borrow_mut()
-            .define(&"Five".to_string(), Literal::Number(77.8));
+        e.borrow_mut().define("Five", Literal::Number(77.8));
 
         let mut f = Environment::new_with_enclosing(Rc::clone(&e));
-        let five_tok = Token::new(TokenType::Identifier, "Five".to_string(), None, 0);
+        let five_tok = Token::new(TokenType::Identifier, "Five".to_string(), None, 0, 1);
         assert!(f.assign(&five_tok, Literal::Number(91.2)).is_ok());
         assert_eq!(f.get(&five_tok).unwrap(), Literal::Number(91.2));
     }
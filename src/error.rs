@@ -1,22 +1,99 @@
+use std::cell::RefCell;
+use std::fmt;
+
 use crate::token::*;
 use crate::token_type::*;
 
+thread_local! {
+    // While capturing, reported errors are collected here instead of
+    // being printed to stderr.
+    static CAPTURED: RefCell<Option<Vec<LoxError>>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    Scan,
+    Parse,
+    Type,
+    Runtime,
+    System,
+}
+
+// An error as reported to the user, detached from the tokens and control
+// flow that `LoxResult` carries. `lexeme` is `None` for errors at the end
+// of input or without a token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoxError {
+    pub kind: ErrorKind,
+    pub line: usize,
+    pub column: usize,
+    pub lexeme: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for LoxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.kind, &self.lexeme) {
+            (ErrorKind::Scan, _) => {
+                write!(
+                    f,
+                    "[line {}:{}] Error: {}",
+                    self.line, self.column, self.message
+                )
+            }
+            (ErrorKind::System, _) => write!(f, "System Error: {}", self.message),
+            (_, Some(lexeme)) => write!(
+                f,
+                "line {}:{} at '{}' {}",
+                self.line, self.column, lexeme, self.message
+            ),
+            (_, None) => write!(f, "{}:{} at end {}", self.line, self.column, self.message),
+        }
+    }
+}
+
+// Collects the errors reported while `f` runs instead of printing them.
+pub fn capture_errors<T>(f: impl FnOnce() -> T) -> (T, Vec<LoxError>) {
+    let outer = CAPTURED.with(|captured| captured.borrow_mut().replace(Vec::new()));
+    let result = f();
+    let errors = CAPTURED.with(|captured| std::mem::replace(&mut *captured.borrow_mut(), outer));
+    (result, errors.unwrap_or_default())
+}
+
 #[derive(Debug)]
 pub enum LoxResult {
-    ParseError { token: Token, message: String },
-    RuntimeError { token: Token, message: String },
-    Error { line: usize, message: String },
-    SystemError { message: String },
-    Break,
+    ParseError {
+        token: Token,
+        message: String,
+    },
+    RuntimeError {
+        token: Token,
+        message: String,
+    },
+    TypeError {
+        token: Token,
+        message: String,
+    },
+    Error {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    SystemError {
+        message: String,
+    },
+    Break(Option<String>),
+    Continue(Option<String>),
 }
 
 impl LoxResult {
-    pub fn error(line: usize, message: &str) -> LoxResult {
+    pub fn error(line: usize, column: usize, message: &str) -> LoxResult {
         let err = LoxResult::Error {
             line,
+            column,
             message: message.to_string(),
         };
-        err.report("");
+        err.report();
         err
     }
 
@@ -25,7 +102,7 @@ impl LoxResult {
             token: token.dup(),
             message: message.to_string(),
         };
-        err.report("");
+        err.report();
         err
     }
 
@@ -34,7 +111,16 @@ impl LoxResult {
             token: token.dup(),
             message: message.to_string(),
         };
-        err.report("");
+        err.report();
+        err
+    }
+
+    pub fn type_error(token: &Token, message: &str) -> LoxResult {
+        let err = LoxResult::TypeError {
+            token: token.dup(),
+            message: message.to_string(),
+        };
+        err.report();
         err
     }
 
@@ -42,27 +128,56 @@ impl LoxResult {
         let err = LoxResult::SystemError {
             message: message.to_string(),
         };
-        err.report("");
+        err.report();
         err
     }
 
-    fn report(&self, loc: &str) {
-        match self {
-            LoxResult::ParseError { token, message }
-            | LoxResult::RuntimeError { token, message } => {
-                if token.is(TokenType::Eof) {
-                    eprintln!("{} at end {}", token.line, message);
-                } else {
-                    eprintln!("line {} at '{}' {}", token.line, token.as_string(), message);
-                }
-            }
-            LoxResult::Error { line, message } => {
-                eprintln!("[line {}] Error{}: {}", line, loc, message);
+    fn report(&self) {
+        let Some(error) = self.to_error() else {
+            return;
+        };
+        CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+            Some(errors) => errors.push(error),
+            None => eprintln!("{}", error),
+        });
+    }
+
+    // `None` for the variants that only unwind control flow
+    pub fn to_error(&self) -> Option<LoxError> {
+        let (kind, token, message) = match self {
+            LoxResult::ParseError { token, message } => (ErrorKind::Parse, token, message),
+            LoxResult::RuntimeError { token, message } => (ErrorKind::Runtime, token, message),
+            LoxResult::TypeError { token, message } => (ErrorKind::Type, token, message),
+            LoxResult::Error {
+                line,
+                column,
+                message,
+            } => {
+                return Some(LoxError {
+                    kind: ErrorKind::Scan,
+                    line: *line,
+                    column: *column,
+                    lexeme: None,
+                    message: message.clone(),
+                })
             }
             LoxResult::SystemError { message } => {
-                eprintln!("System Error: {message}");
+                return Some(LoxError {
+                    kind: ErrorKind::System,
+                    line: 0,
+                    column: 0,
+                    lexeme: None,
+                    message: message.clone(),
+                })
             }
-            LoxResult::Break => {}
+            LoxResult::Break(_) | LoxResult::Continue(_) => return None,
         };
+        Some(LoxError {
+            kind,
+            line: token.line,
+            column: token.column,
+            lexeme: (!token.is(TokenType::Eof)).then(|| token.as_string().to_string()),
+            message: message.clone(),
+        })
     }
 }
@@ -7,6 +7,7 @@ pub enum Expr {
     Assign(AssignExpr),
     Binary(BinaryExpr),
     Call(CallExpr),
+    Get(GetExpr),
     Grouping(GroupingExpr),
     Literal(LiteralExpr),
     Logical(LogicalExpr),
@@ -20,6 +21,7 @@ impl Expr {
             Expr::Assign(v) => v.accept(expr_visitor),
             Expr::Binary(v) => v.accept(expr_visitor),
             Expr::Call(v) => v.accept(expr_visitor),
+            Expr::Get(v) => v.accept(expr_visitor),
             Expr::Grouping(v) => v.accept(expr_visitor),
             Expr::Literal(v) => v.accept(expr_visitor),
             Expr::Logical(v) => v.accept(expr_visitor),
@@ -44,6 +46,13 @@ pub struct CallExpr {
     pub callee: Rc<Expr>,
     pub paren: Token,
     pub arguments: Vec<Expr>,
+    pub optional: bool,
+}
+
+pub struct GetExpr {
+    pub object: Box<Expr>,
+    pub name: Token,
+    pub optional: bool,
 }
 
 pub struct GroupingExpr {
@@ -73,6 +82,7 @@ pub trait ExprVisitor<T> {
     fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<T, LoxResult>;
     fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<T, LoxResult>;
     fn visit_call_expr(&self, expr: &CallExpr) -> Result<T, LoxResult>;
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<T, LoxResult>;
     fn visit_grouping_expr(&self, expr: &GroupingExpr) -> Result<T, LoxResult>;
     fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<T, LoxResult>;
     fn visit_logical_expr(&self, expr: &LogicalExpr) -> Result<T, LoxResult>;
@@ -98,6 +108,12 @@ impl CallExpr {
     }
 }
 
+impl GetExpr {
+    pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxResult> {
+        visitor.visit_get_expr(self)
+    }
+}
+
 impl GroupingExpr {
     pub fn accept<T>(&self, visitor: &dyn ExprVisitor<T>) -> Result<T, LoxResult> {
         visitor.visit_grouping_expr(self)
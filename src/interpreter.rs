@@ -1,21 +1,34 @@
 use std::cell::RefCell;
+use std::io::{self, BufRead};
 use std::rc::Rc;
 
-use crate::callable::*;
 use crate::environment::Environment;
 use crate::error::*;
 use crate::expr::*;
 use crate::literal::*;
+use crate::lox_enum::*;
+use crate::native_fs::*;
 use crate::native_functions::*;
+use crate::native_io::*;
+use crate::native_process::*;
 use crate::stmt::*;
+use crate::token::*;
 use crate::token_type::*;
 
+// Largest integer such that it and all smaller ones are exactly representable as f64
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
 pub struct Interpreter {
     globals: Rc<RefCell<Environment>>,
     // RefCell because we want to mutate the environment
     // outer RefCell to avoid cyclic reference when replacing self.environment
     environment: RefCell<Rc<RefCell<Environment>>>,
     nest: RefCell<usize>,
+    asserts_enabled: bool,
+    fs_permissions: FsPermissions,
+    script_args: Vec<String>,
+    // where the stdin natives read from; `None` for the process's stdin
+    stdin: RefCell<Option<Box<dyn BufRead>>>,
 }
 
 impl StmtVisitor<()> for Interpreter {
@@ -26,24 +39,55 @@ impl StmtVisitor<()> for Interpreter {
                 "break outside of while/for loop",
             ))
         } else {
-            Err(LoxResult::Break)
+            Err(LoxResult::Break(
+                stmt.label.as_ref().map(|l| l.as_string().to_string()),
+            ))
         }
     }
 
-    fn visit_while_stmt(&self, stmt: &WhileStmt) -> Result<(), LoxResult> {
-        *self.nest.borrow_mut() += 1;
-        while self.is_truthy(&self.evaluate(&stmt.condition)?) {
-            match self.execute(&stmt.body) {
-                Err(LoxResult::Break) => break,
-                Err(e) => return Err(e),
-                Ok(_) => {}
-            }
+    fn visit_continue_stmt(&self, stmt: &ContinueStmt) -> Result<(), LoxResult> {
+        if *self.nest.borrow() == 0 {
+            Err(LoxResult::runtime_error(
+                &stmt.token,
+                "continue outside of while/for loop",
+            ))
+        } else {
+            Err(LoxResult::Continue(
+                stmt.label.as_ref().map(|l| l.as_string().to_string()),
+            ))
         }
+    }
+
+    fn visit_do_stmt(&self, stmt: &DoStmt) -> Result<(), LoxResult> {
+        *self.nest.borrow_mut() += 1;
+        let result = self.run_do_loop(stmt);
         *self.nest.borrow_mut() -= 1;
+        result
+    }
 
+    fn visit_enum_stmt(&self, stmt: &EnumStmt) -> Result<(), LoxResult> {
+        let enumeration = LoxEnum {
+            name: stmt.name.as_string().to_string(),
+            members: stmt
+                .members
+                .iter()
+                .map(|m| m.as_string().to_string())
+                .collect(),
+        };
+        self.environment
+            .borrow()
+            .borrow_mut()
+            .define(stmt.name.as_string(), Literal::Enum(Rc::new(enumeration)));
         Ok(())
     }
 
+    fn visit_while_stmt(&self, stmt: &WhileStmt) -> Result<(), LoxResult> {
+        *self.nest.borrow_mut() += 1;
+        let result = self.run_loop(stmt);
+        *self.nest.borrow_mut() -= 1;
+        result
+    }
+
     fn visit_if_stmt(&self, stmt: &IfStmt) -> Result<(), LoxResult> {
         if self.is_truthy(&self.evaluate(&stmt.condition)?) {
             self.execute(&stmt.then_branch)
@@ -54,6 +98,22 @@ impl StmtVisitor<()> for Interpreter {
         }
     }
 
+    fn visit_assert_stmt(&self, stmt: &AssertStmt) -> Result<(), LoxResult> {
+        if !self.asserts_enabled || self.is_truthy(&self.evaluate(&stmt.condition)?) {
+            return Ok(());
+        }
+
+        let message = match &stmt.message {
+            Some(message) => format!(
+                "Assertion failed: {}: {}",
+                stmt.source,
+                self.evaluate(message)?
+            ),
+            None => format!("Assertion failed: {}", stmt.source),
+        };
+        Err(LoxResult::runtime_error(&stmt.keyword, &message))
+    }
+
     fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<(), LoxResult> {
         let e = Environment::new_with_enclosing(self.environment.borrow().clone());
         self.execute_block(&stmt.statements, e)
@@ -79,7 +139,7 @@ impl StmtVisitor<()> for Interpreter {
         self.environment
             .borrow()
             .borrow_mut()
-            .define(&stmt.name.as_string(), value);
+            .define(stmt.name.as_string(), value);
         Ok(())
     }
 }
@@ -91,31 +151,11 @@ impl ExprVisitor<Literal> for Interpreter {
     }
 
     fn visit_call_expr(&self, expr: &CallExpr) -> Result<Literal, LoxResult> {
-        let callee = self.evaluate(&expr.callee)?;
-
-        let mut arguments = Vec::new();
-        for argument in &expr.arguments {
-            arguments.push(self.evaluate(argument)?);
-        }
+        Ok(self.call_chain(expr)?.unwrap_or(Literal::Nil))
+    }
 
-        if let Literal::Func(function) = callee {
-            if arguments.len() != function.func.arity() {
-                return Err(LoxResult::runtime_error(
-                    &expr.paren,
-                    &format!(
-                        "Expected {} arguments but got {}.",
-                        function.func.arity(),
-                        arguments.len()
-                    ),
-                ));
-            }
-            function.func.call(self, arguments)
-        } else {
-            Err(LoxResult::runtime_error(
-                &expr.paren,
-                "Can only call functions and classes",
-            ))
-        }
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<Literal, LoxResult> {
+        Ok(self.get_chain(expr)?.unwrap_or(Literal::Nil))
     }
 
     fn visit_logical_expr(&self, expr: &LogicalExpr) -> Result<Literal, LoxResult> {
@@ -141,6 +181,20 @@ impl ExprVisitor<Literal> for Interpreter {
         let right: Literal = self.evaluate(&expr.right)?;
         let op = expr.operator.token_type();
 
+        if matches!(
+            op,
+            TokenType::Ampersand
+                | TokenType::Pipe
+                | TokenType::Caret
+                | TokenType::LessLess
+                | TokenType::GreaterGreater
+        ) {
+            return self.bitwise(&expr.operator, &left, &right);
+        }
+
+        // created lazily, since creating a LoxResult reports it
+        let illegal = || LoxResult::runtime_error(&expr.operator, "Illegal expression");
+
         let result = match (left, right) {
             (Literal::Number(left), Literal::Number(right)) => match op {
                 TokenType::Minus => Literal::Number(left - right),
@@ -153,50 +207,57 @@ impl ExprVisitor<Literal> for Interpreter {
                 TokenType::LessEqual => Literal::Bool(left <= right),
                 TokenType::BangEqual => Literal::Bool(left != right),
                 TokenType::EqualEqual => Literal::Bool(left == right),
-                _ => {
-                    todo!("need to work on your code dude");
-                }
+                _ => return Err(illegal()),
             },
             (Literal::Number(left), Literal::String(right)) => match op {
                 TokenType::Plus => Literal::String(format!("{left}{right}")),
-                _ => Literal::ArithmeticError,
+                _ => return Err(illegal()),
             },
             (Literal::String(left), Literal::Number(right)) => match op {
                 TokenType::Plus => Literal::String(format!("{left}{right}")),
-                _ => Literal::ArithmeticError,
+                _ => return Err(illegal()),
             },
             (Literal::String(left), Literal::String(right)) => match op {
                 TokenType::Plus => Literal::String(format!("{left}{right}")),
                 TokenType::BangEqual => Literal::Bool(left != right),
                 TokenType::EqualEqual => Literal::Bool(left == right),
-                _ => Literal::ArithmeticError,
+                _ => return Err(illegal()),
             },
             (Literal::Bool(left), Literal::Bool(right)) => match op {
                 TokenType::BangEqual => Literal::Bool(left != right),
                 TokenType::EqualEqual => Literal::Bool(left == right),
-                _ => Literal::ArithmeticError,
+                _ => return Err(illegal()),
+            },
+            (Literal::EnumMember(left), Literal::EnumMember(right)) => match op {
+                TokenType::BangEqual => Literal::Bool(left != right),
+                TokenType::EqualEqual => Literal::Bool(left == right),
+                _ => return Err(illegal()),
+            },
+            // sets and regexes are equal only to themselves
+            (Literal::Set(left), Literal::Set(right)) => match op {
+                TokenType::BangEqual => Literal::Bool(!Rc::ptr_eq(&left, &right)),
+                TokenType::EqualEqual => Literal::Bool(Rc::ptr_eq(&left, &right)),
+                _ => return Err(illegal()),
+            },
+            (Literal::Regex(left), Literal::Regex(right)) => match op {
+                TokenType::BangEqual => Literal::Bool(!Rc::ptr_eq(&left, &right)),
+                TokenType::EqualEqual => Literal::Bool(Rc::ptr_eq(&left, &right)),
+                _ => return Err(illegal()),
             },
             (Literal::Nil, Literal::Nil) => match op {
                 TokenType::BangEqual => Literal::Bool(false),
                 TokenType::EqualEqual => Literal::Bool(true),
-                _ => Literal::ArithmeticError,
+                _ => return Err(illegal()),
             },
-            (Literal::Nil, _) => match op {
+            (Literal::Nil, _) | (_, Literal::Nil) => match op {
                 TokenType::EqualEqual => Literal::Bool(false),
                 TokenType::BangEqual => Literal::Bool(true),
-                _ => Literal::ArithmeticError,
+                _ => return Err(illegal()),
             },
-            _ => Literal::ArithmeticError,
+            _ => return Err(illegal()),
         };
 
-        if result == Literal::ArithmeticError {
-            Err(LoxResult::runtime_error(
-                &expr.operator,
-                "Illegal expression",
-            ))
-        } else {
-            Ok(result)
-        }
+        Ok(result)
     }
 
     fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<Literal, LoxResult> {
@@ -204,8 +265,8 @@ impl ExprVisitor<Literal> for Interpreter {
 
         match expr.operator.token_type() {
             TokenType::Minus => match right {
-                Literal::Number(n) => return Ok(Literal::Number(-n)),
-                _ => return Ok(Literal::Nil),
+                Literal::Number(n) => Ok(Literal::Number(-n)),
+                _ => Ok(Literal::Nil),
             },
             TokenType::Bang => {
                 if self.is_truthy(&right) {
@@ -214,7 +275,10 @@ impl ExprVisitor<Literal> for Interpreter {
                     Ok(Literal::Bool(true))
                 }
             }
-            _ => Err(LoxResult::error(0, "Unreachable according to Nystrom")),
+            TokenType::Tilde => Ok(Literal::Number(
+                !self.integral(&expr.operator, &right)? as f64
+            )),
+            _ => Err(LoxResult::error(0, 0, "Unreachable according to Nystrom")),
         }
     }
 
@@ -232,21 +296,176 @@ impl ExprVisitor<Literal> for Interpreter {
     }
 }
 
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
 impl Interpreter {
     pub fn new() -> Interpreter {
         let globals = Rc::new(RefCell::new(Environment::new()));
-
-        globals.borrow_mut().define(
-            "clock",
-            Literal::Func(Callable {
-                func: Rc::new(NativeClock {}),
-            }),
-        );
+        define_natives(&mut globals.borrow_mut());
 
         Interpreter {
             globals: Rc::clone(&globals),
-            environment: RefCell::new(Rc::clone(&globals)),
+            environment: RefCell::new(globals),
             nest: RefCell::new(0),
+            asserts_enabled: true,
+            fs_permissions: FsPermissions::default(),
+            script_args: Vec::new(),
+            stdin: RefCell::new(None),
+        }
+    }
+
+    // Links of a `?.` chain evaluate to `None` once an optional link meets
+    // nil, so the whole chain short-circuits to nil instead of erroring
+    // on the links after it.
+    fn evaluate_chain(&self, expr: &Expr) -> Result<Option<Literal>, LoxResult> {
+        match expr {
+            Expr::Call(call) => self.call_chain(call),
+            Expr::Get(get) => self.get_chain(get),
+            _ => Ok(Some(self.evaluate(expr)?)),
+        }
+    }
+
+    fn call_chain(&self, expr: &CallExpr) -> Result<Option<Literal>, LoxResult> {
+        let callee = match self.evaluate_chain(&expr.callee)? {
+            Some(Literal::Nil) if expr.optional => return Ok(None),
+            Some(callee) => callee,
+            None => return Ok(None),
+        };
+
+        let mut arguments = Vec::new();
+        for argument in &expr.arguments {
+            arguments.push(self.evaluate(argument)?);
+        }
+
+        if let Literal::Func(function) = callee {
+            let arity = function.func.arity();
+            if function.func.is_variadic() && arguments.len() < arity {
+                return Err(LoxResult::runtime_error(
+                    &expr.paren,
+                    &format!(
+                        "Expected at least {} arguments but got {}.",
+                        arity,
+                        arguments.len()
+                    ),
+                ));
+            }
+            if !function.func.is_variadic() && arguments.len() != arity {
+                return Err(LoxResult::runtime_error(
+                    &expr.paren,
+                    &format!("Expected {} arguments but got {}.", arity, arguments.len()),
+                ));
+            }
+            function.func.call(self, arguments, &expr.paren).map(Some)
+        } else {
+            Err(LoxResult::runtime_error(
+                &expr.paren,
+                "Can only call functions and classes",
+            ))
+        }
+    }
+
+    fn get_chain(&self, expr: &GetExpr) -> Result<Option<Literal>, LoxResult> {
+        match self.evaluate_chain(&expr.object)? {
+            Some(Literal::Nil) if expr.optional => Ok(None),
+            None => Ok(None),
+            Some(object) => self.get_property(&object, &expr.name).map(Some),
+        }
+    }
+
+    pub fn get_property(&self, object: &Literal, name: &Token) -> Result<Literal, LoxResult> {
+        if let Some(value) = Self::lookup_property(object, name.as_string()) {
+            return Ok(value);
+        }
+
+        let message = match object {
+            Literal::Enum(enumeration) => format!(
+                "Undefined member '{}' of enum {}.",
+                name.as_string(),
+                enumeration.name
+            ),
+            Literal::EnumMember(_) | Literal::Regex(_) => {
+                format!("Undefined property '{}'.", name.as_string())
+            }
+            _ => "Only instances have properties.".to_string(),
+        };
+        Err(LoxResult::runtime_error(name, &message))
+    }
+
+    // Looks up a property without reporting anything when it is missing.
+    pub fn lookup_property(object: &Literal, name: &str) -> Option<Literal> {
+        match object {
+            Literal::Enum(enumeration) => {
+                LoxEnum::member(enumeration, name).map(Literal::EnumMember)
+            }
+            Literal::EnumMember(member) => match name {
+                "name" => Some(Literal::String(member.name().to_string())),
+                "ordinal" => Some(Literal::Number(member.ordinal as f64)),
+                _ => None,
+            },
+            Literal::Regex(regex) => match name {
+                "pattern" => Some(Literal::String(regex.pattern.clone())),
+                "groups" => Some(Literal::Number(regex.groups() as f64)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // `assert` statements are skipped entirely, conditions included
+    pub fn disable_asserts(&mut self) {
+        self.asserts_enabled = false;
+    }
+
+    // Lets the file system natives access paths under `prefix`
+    pub fn allow_fs(&mut self, access: Access, prefix: &str) {
+        self.fs_permissions.allow(access, prefix);
+    }
+
+    pub fn fs_permissions(&self) -> &FsPermissions {
+        &self.fs_permissions
+    }
+
+    // The command line arguments following the script's path
+    pub fn set_script_args(&mut self, args: Vec<String>) {
+        self.script_args = args;
+    }
+
+    pub fn script_args(&self) -> &[String] {
+        &self.script_args
+    }
+
+    // Defines `exit`, `getenv` and the script argument natives. Off by
+    // default, since `exit` ends the whole host process.
+    pub fn allow_process(&mut self) {
+        for native in &PROCESS_NATIVES {
+            native.define(&mut self.globals.borrow_mut());
+        }
+    }
+
+    // Defines the natives that read stdin, which an embedding host may be
+    // using itself.
+    pub fn allow_stdin(&mut self) {
+        for native in &IO_NATIVES {
+            native.define(&mut self.globals.borrow_mut());
+        }
+    }
+
+    // Makes the stdin natives read from `reader` instead of the process's
+    // stdin.
+    pub fn set_stdin(&mut self, reader: impl BufRead + 'static) {
+        *self.stdin.get_mut() = Some(Box::new(reader));
+    }
+
+    // The process's stdin is locked only for the length of `read`, so the
+    // REPL can still read from it between statements.
+    pub(crate) fn read_stdin<T>(&self, read: impl FnOnce(&mut dyn BufRead) -> T) -> T {
+        match self.stdin.borrow_mut().as_mut() {
+            Some(reader) => read(reader),
+            None => read(&mut io::stdin().lock()),
         }
     }
 
@@ -272,28 +491,142 @@ impl Interpreter {
         result
     }
 
+    // an unlabeled jump targets the innermost loop, a labeled one
+    // unwinds until it reaches the loop carrying that label
+    fn is_jump_target(target: &Option<String>, label: &Option<Token>) -> bool {
+        match (target, label) {
+            (None, _) => true,
+            (Some(target), Some(label)) => target == label.as_string(),
+            (Some(_), None) => false,
+        }
+    }
+
+    fn run_loop(&self, stmt: &WhileStmt) -> Result<(), LoxResult> {
+        while self.is_truthy(&self.evaluate(&stmt.condition)?) {
+            match self.execute(&stmt.body) {
+                Err(LoxResult::Break(target)) if Self::is_jump_target(&target, &stmt.label) => {
+                    break
+                }
+                Err(LoxResult::Continue(target)) if Self::is_jump_target(&target, &stmt.label) => {}
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+            if let Some(increment) = &stmt.increment {
+                self.evaluate(increment)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run_do_loop(&self, stmt: &DoStmt) -> Result<(), LoxResult> {
+        loop {
+            match self.execute(&stmt.body) {
+                Err(LoxResult::Break(target)) if Self::is_jump_target(&target, &stmt.label) => {
+                    break
+                }
+                Err(LoxResult::Continue(target)) if Self::is_jump_target(&target, &stmt.label) => {}
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+            if !self.is_truthy(&self.evaluate(&stmt.condition)?) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Numbers are f64, so bitwise operands must be integers that f64 holds
+    // exactly; anything else would not round-trip through the operation.
+    fn integral(&self, operator: &Token, value: &Literal) -> Result<i64, LoxResult> {
+        match value {
+            Literal::Number(n) if n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER => Ok(*n as i64),
+            _ => Err(LoxResult::runtime_error(
+                operator,
+                "Operands must be integers.",
+            )),
+        }
+    }
+
+    fn bitwise(
+        &self,
+        operator: &Token,
+        left: &Literal,
+        right: &Literal,
+    ) -> Result<Literal, LoxResult> {
+        let left = self.integral(operator, left)?;
+        let right = self.integral(operator, right)?;
+        let shift = matches!(
+            operator.token_type(),
+            TokenType::LessLess | TokenType::GreaterGreater
+        );
+        if shift && right < 0 {
+            return Err(LoxResult::runtime_error(
+                operator,
+                "Shift count must not be negative.",
+            ));
+        }
+
+        // Counts of 64 and up shift every bit out: `x >> 64` is 0 or -1,
+        // and `x << 64` is 0 for 0 and out of range otherwise.
+        let count = right.clamp(0, 63) as u32;
+        let result = match operator.token_type() {
+            TokenType::Ampersand => Some(left & right),
+            TokenType::Pipe => Some(left | right),
+            TokenType::Caret => Some(left ^ right),
+            TokenType::LessLess => Some(left << count).filter(|n| n >> count == left),
+            TokenType::GreaterGreater => Some(left >> count),
+            _ => None,
+        };
+
+        match result {
+            Some(n) if (n as f64).abs() <= MAX_SAFE_INTEGER => Ok(Literal::Number(n as f64)),
+            _ => Err(LoxResult::runtime_error(
+                operator,
+                "Result of bitwise operation is out of range.",
+            )),
+        }
+    }
+
     // Lox follows Ruby’s simple rule: false and nil are falsey, and everything else is truthy
-    fn is_truthy(&self, literal: &Literal) -> bool {
+    pub fn is_truthy(&self, literal: &Literal) -> bool {
         !matches!(literal, Literal::Nil | Literal::Bool(false))
     }
 
-    pub fn interpret(&self, statements: &[Stmt]) -> bool {
-        let mut success = true;
+    // Runs `statements`, stopping at the first error. The result is the
+    // value of the last statement if that is an expression statement, and
+    // nil otherwise.
+    pub(crate) fn run(&self, statements: &[Stmt]) -> Result<Literal, LoxResult> {
         *self.nest.borrow_mut() = 0;
-        for statement in statements {
-            if self.execute(statement).is_err() {
-                success = false;
-                break;
-            }
+        let Some((last, rest)) = statements.split_last() else {
+            return Ok(Literal::Nil);
+        };
+        for statement in rest {
+            self.execute(statement)?;
+        }
+        match last {
+            Stmt::Expression(stmt) => self.evaluate(&stmt.expression),
+            _ => self.execute(last).map(|_| Literal::Nil),
         }
-        success
+    }
+
+    pub(crate) fn global(&self, name: &str) -> Option<Literal> {
+        self.globals.borrow().values.get(name).cloned()
+    }
+
+    // Defines or replaces a global variable, visible to everything run
+    // afterwards.
+    pub fn define_global(&self, name: &str, value: Literal) {
+        self.globals.borrow_mut().define(name, value);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::token::*;
+    use crate::lox_set::*;
+    use crate::regex::*;
 
     fn make_literal(o: Literal) -> Box<Expr> {
         Box::new(Expr::Literal(LiteralExpr { value: Some(o) }))
@@ -307,7 +640,7 @@ mod tests {
     fn test_unary_minus() {
         let terp = Interpreter::new();
         let unary_expr = UnaryExpr {
-            operator: Token::new(TokenType::Minus, "-".to_string(), None, 123),
+            operator: Token::new(TokenType::Minus, "-".to_string(), None, 123, 1),
             right: make_literal(Literal::Number(123.0)),
         };
         let result = terp.visit_unary_expr(&unary_expr);
@@ -319,7 +652,7 @@ mod tests {
     fn test_unary_not() {
         let terp = Interpreter::new();
         let unary_expr = UnaryExpr {
-            operator: Token::new(TokenType::Bang, "!".to_string(), None, 123),
+            operator: Token::new(TokenType::Bang, "!".to_string(), None, 123, 1),
             right: make_literal(Literal::Bool(false)),
         };
         let result = terp.visit_unary_expr(&unary_expr);
@@ -332,7 +665,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal(Literal::Number(15.0)),
-            operator: Token::new(TokenType::Minus, "-".to_string(), None, 123),
+            operator: Token::new(TokenType::Minus, "-".to_string(), None, 123, 1),
             right: make_literal(Literal::Number(7.0)),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -345,7 +678,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal(Literal::Number(15.0)),
-            operator: Token::new(TokenType::Star, "*".to_string(), None, 123),
+            operator: Token::new(TokenType::Star, "*".to_string(), None, 123, 1),
             right: make_literal(Literal::Number(7.0)),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -358,7 +691,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal(Literal::Number(21.0)),
-            operator: Token::new(TokenType::Slash, "/".to_string(), None, 123),
+            operator: Token::new(TokenType::Slash, "/".to_string(), None, 123, 1),
             right: make_literal(Literal::Number(7.0)),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -371,7 +704,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal(Literal::Number(21.0)),
-            operator: Token::new(TokenType::Plus, "+".to_string(), None, 123),
+            operator: Token::new(TokenType::Plus, "+".to_string(), None, 123, 1),
             right: make_literal(Literal::Number(7.0)),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -384,7 +717,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal_string("hello, "),
-            operator: Token::new(TokenType::Plus, "+".to_string(), None, 123),
+            operator: Token::new(TokenType::Plus, "+".to_string(), None, 123, 1),
             right: make_literal_string("world!"),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -400,7 +733,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal(Literal::Number(15.0)),
-            operator: Token::new(TokenType::Minus, "-".to_string(), None, 123),
+            operator: Token::new(TokenType::Minus, "-".to_string(), None, 123, 1),
             right: make_literal(Literal::Bool(true)),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -412,7 +745,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal(Literal::Number(15.0)),
-            operator: Token::new(TokenType::Greater, ">".to_string(), None, 123),
+            operator: Token::new(TokenType::Greater, ">".to_string(), None, 123, 1),
             right: make_literal(Literal::Bool(true)),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -444,7 +777,7 @@ mod tests {
     #[test]
     fn test_less_than() {
         run_comparison_test(
-            &Token::new(TokenType::Less, "<".to_string(), None, 123),
+            &Token::new(TokenType::Less, "<".to_string(), None, 123, 1),
             vec![true, false, false],
         );
     }
@@ -452,7 +785,7 @@ mod tests {
     #[test]
     fn test_less_than_or_equal_to() {
         run_comparison_test(
-            &Token::new(TokenType::LessEqual, "<=".to_string(), None, 123),
+            &Token::new(TokenType::LessEqual, "<=".to_string(), None, 123, 1),
             vec![true, true, false],
         );
     }
@@ -460,7 +793,7 @@ mod tests {
     #[test]
     fn test_greater_than() {
         run_comparison_test(
-            &Token::new(TokenType::Greater, ">".to_string(), None, 123),
+            &Token::new(TokenType::Greater, ">".to_string(), None, 123, 1),
             vec![false, false, true],
         );
     }
@@ -468,7 +801,7 @@ mod tests {
     #[test]
     fn test_greater_than_or_equal_to() {
         run_comparison_test(
-            &Token::new(TokenType::GreaterEqual, ">=".to_string(), None, 123),
+            &Token::new(TokenType::GreaterEqual, ">=".to_string(), None, 123, 1),
             vec![false, true, true],
         );
     }
@@ -476,7 +809,7 @@ mod tests {
     #[test]
     fn test_equals_nums() {
         run_comparison_test(
-            &Token::new(TokenType::EqualEqual, "==".to_string(), None, 123),
+            &Token::new(TokenType::EqualEqual, "==".to_string(), None, 123, 1),
             vec![false, true, false],
         );
     }
@@ -484,7 +817,7 @@ mod tests {
     #[test]
     fn test_not_equals_nums() {
         run_comparison_test(
-            &Token::new(TokenType::BangEqual, "!=".to_string(), None, 123),
+            &Token::new(TokenType::BangEqual, "!=".to_string(), None, 123, 1),
             vec![true, false, true],
         );
     }
@@ -494,7 +827,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal_string("hello"),
-            operator: Token::new(TokenType::EqualEqual, "==".to_string(), None, 123),
+            operator: Token::new(TokenType::EqualEqual, "==".to_string(), None, 123, 1),
             right: make_literal_string("hellx"),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -507,7 +840,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal_string("world"),
-            operator: Token::new(TokenType::EqualEqual, "==".to_string(), None, 123),
+            operator: Token::new(TokenType::EqualEqual, "==".to_string(), None, 123, 1),
             right: make_literal_string("world"),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -520,7 +853,7 @@ mod tests {
         let terp = Interpreter::new();
         let binary_expr = BinaryExpr {
             left: make_literal(Literal::Nil),
-            operator: Token::new(TokenType::EqualEqual, "==".to_string(), None, 123),
+            operator: Token::new(TokenType::EqualEqual, "==".to_string(), None, 123, 1),
             right: make_literal(Literal::Nil),
         };
         let result = terp.visit_binary_expr(&binary_expr);
@@ -528,12 +861,25 @@ mod tests {
         assert_eq!(result.ok(), Some(Literal::Bool(true)));
     }
 
+    #[test]
+    fn test_value_not_equals_nil() {
+        let terp = Interpreter::new();
+        let binary_expr = BinaryExpr {
+            left: make_literal(Literal::String("line".to_string())),
+            operator: Token::new(TokenType::BangEqual, "!=".to_string(), None, 123, 1),
+            right: make_literal(Literal::Nil),
+        };
+        let result = terp.visit_binary_expr(&binary_expr);
+        assert_eq!(result.ok(), Some(Literal::Bool(true)));
+    }
+
     #[test]
     fn test_var_stmt_defined() {
         let terp = Interpreter::new();
-        let name = Token::new(TokenType::Identifier, "foo".to_string(), None, 123);
+        let name = Token::new(TokenType::Identifier, "foo".to_string(), None, 123, 1);
         let var_stmt = VarStmt {
             name: name.dup(),
+            type_annotation: None,
             initializer: Some(*make_literal(Literal::Number(23.0))),
         };
         assert!(terp.visit_var_stmt(&var_stmt).is_ok());
@@ -546,9 +892,10 @@ mod tests {
     #[test]
     fn test_var_stmt_undefined() {
         let terp = Interpreter::new();
-        let name = Token::new(TokenType::Identifier, "foo".to_string(), None, 123);
+        let name = Token::new(TokenType::Identifier, "foo".to_string(), None, 123, 1);
         let var_stmt = VarStmt {
             name: name.dup(),
+            type_annotation: None,
             initializer: None,
         };
         assert!(terp.visit_var_stmt(&var_stmt).is_ok());
@@ -561,9 +908,10 @@ mod tests {
     #[test]
     fn test_variable_expr() {
         let terp = Interpreter::new();
-        let name = Token::new(TokenType::Identifier, "foo".to_string(), None, 123);
+        let name = Token::new(TokenType::Identifier, "foo".to_string(), None, 123, 1);
         let var_stmt = VarStmt {
             name: name.dup(),
+            type_annotation: None,
             initializer: Some(*make_literal(Literal::Number(23.0))),
         };
         assert!(terp.visit_var_stmt(&var_stmt).is_ok());
@@ -578,8 +926,276 @@ mod tests {
     #[test]
     fn test_undefined_variable_expr() {
         let terp = Interpreter::new();
-        let name = Token::new(TokenType::Identifier, "foo".to_string(), None, 123);
+        let name = Token::new(TokenType::Identifier, "foo".to_string(), None, 123, 1);
         let var_expr = VariableExpr { name: name.dup() };
         assert!(terp.visit_variable_expr(&var_expr).is_err());
     }
+
+    fn make_get(object: Box<Expr>, name: &str, optional: bool) -> Box<Expr> {
+        Box::new(Expr::Get(GetExpr {
+            object,
+            name: Token::new(TokenType::Identifier, name.to_string(), None, 123, 1),
+            optional,
+        }))
+    }
+
+    #[test]
+    fn test_optional_get_on_nil() {
+        let terp = Interpreter::new();
+        let get_expr = make_get(make_literal(Literal::Nil), "foo", true);
+        assert_eq!(terp.evaluate(&get_expr).ok(), Some(Literal::Nil));
+    }
+
+    #[test]
+    fn test_get_on_nil_is_error() {
+        let terp = Interpreter::new();
+        let get_expr = make_get(make_literal(Literal::Nil), "foo", false);
+        assert!(terp.evaluate(&get_expr).is_err());
+    }
+
+    #[test]
+    fn test_optional_chain_short_circuits() {
+        let terp = Interpreter::new();
+        // nil?.foo.bar()
+        let get_expr = make_get(
+            make_get(make_literal(Literal::Nil), "foo", true),
+            "bar",
+            false,
+        );
+        let call_expr = Expr::Call(CallExpr {
+            callee: Rc::new(*get_expr),
+            paren: Token::new(TokenType::RightParen, ")".to_string(), None, 123, 1),
+            arguments: Vec::new(),
+            optional: false,
+        });
+        assert_eq!(terp.evaluate(&call_expr).ok(), Some(Literal::Nil));
+    }
+
+    fn make_color_enum(terp: &Interpreter) -> Box<Expr> {
+        let name = Token::new(TokenType::Identifier, "Color".to_string(), None, 123, 1);
+        let members = ["Red", "Green", "Blue"]
+            .iter()
+            .map(|m| Token::new(TokenType::Identifier, m.to_string(), None, 123, 1))
+            .collect();
+        let enum_stmt = EnumStmt {
+            name: name.dup(),
+            members,
+        };
+        assert!(terp.visit_enum_stmt(&enum_stmt).is_ok());
+        Box::new(Expr::Variable(VariableExpr { name }))
+    }
+
+    #[test]
+    fn test_enum_member_accessors() {
+        let terp = Interpreter::new();
+        let green = make_get(make_color_enum(&terp), "Green", false);
+        assert_eq!(
+            terp.evaluate(&make_get(green, "ordinal", false)).ok(),
+            Some(Literal::Number(1.0))
+        );
+        let blue = make_get(make_color_enum(&terp), "Blue", false);
+        assert_eq!(
+            terp.evaluate(&make_get(blue, "name", false)).ok(),
+            Some(Literal::String("Blue".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_enum_members_are_comparable() {
+        let terp = Interpreter::new();
+        let color = make_color_enum(&terp);
+        let red = terp.evaluate(&make_get(color, "Red", false)).unwrap();
+        let binary_expr = BinaryExpr {
+            left: make_literal(red.clone()),
+            operator: Token::new(TokenType::EqualEqual, "==".to_string(), None, 123, 1),
+            right: make_literal(red),
+        };
+        assert_eq!(
+            terp.visit_binary_expr(&binary_expr).ok(),
+            Some(Literal::Bool(true))
+        );
+    }
+
+    #[test]
+    fn test_sets_and_regexes_compare_by_identity() {
+        let terp = Interpreter::new();
+        let compare = |ttype, lexeme: &str, left: &Literal, right: &Literal| {
+            let binary_expr = BinaryExpr {
+                left: make_literal(left.clone()),
+                operator: Token::new(ttype, lexeme.to_string(), None, 123, 1),
+                right: make_literal(right.clone()),
+            };
+            terp.visit_binary_expr(&binary_expr).ok()
+        };
+        let set = || Literal::Set(Rc::new(RefCell::new(LoxSet::new())));
+        let regex = || Literal::Regex(Rc::new(Regex::new("a").unwrap()));
+        for (a, b) in [(set(), set()), (regex(), regex())] {
+            let same = Some(Literal::Bool(true));
+            let different = Some(Literal::Bool(false));
+            assert_eq!(compare(TokenType::EqualEqual, "==", &a, &a), same);
+            assert_eq!(compare(TokenType::EqualEqual, "==", &a, &b), different);
+            assert_eq!(compare(TokenType::BangEqual, "!=", &a, &b), same);
+            assert_eq!(compare(TokenType::Less, "<", &a, &a), None);
+        }
+    }
+
+    #[test]
+    fn test_undefined_enum_member() {
+        let terp = Interpreter::new();
+        let purple = make_get(make_color_enum(&terp), "Purple", false);
+        assert!(terp.evaluate(&purple).is_err());
+    }
+
+    fn run_bitwise_test(ttype: TokenType, lexeme: &str, left: f64, right: f64) -> Option<Literal> {
+        let terp = Interpreter::new();
+        let binary_expr = BinaryExpr {
+            left: make_literal(Literal::Number(left)),
+            operator: Token::new(ttype, lexeme.to_string(), None, 123, 1),
+            right: make_literal(Literal::Number(right)),
+        };
+        terp.visit_binary_expr(&binary_expr).ok()
+    }
+
+    #[test]
+    fn test_bitwise_operators() {
+        let n = |n| Some(Literal::Number(n));
+        assert_eq!(
+            run_bitwise_test(TokenType::Ampersand, "&", 6.0, 3.0),
+            n(2.0)
+        );
+        assert_eq!(run_bitwise_test(TokenType::Pipe, "|", 6.0, 3.0), n(7.0));
+        assert_eq!(run_bitwise_test(TokenType::Caret, "^", 6.0, 3.0), n(5.0));
+        assert_eq!(
+            run_bitwise_test(TokenType::LessLess, "<<", 1.0, 4.0),
+            n(16.0)
+        );
+        assert_eq!(
+            run_bitwise_test(TokenType::GreaterGreater, ">>", -16.0, 2.0),
+            n(-4.0)
+        );
+    }
+
+    #[test]
+    fn test_bitwise_requires_integers() {
+        assert_eq!(run_bitwise_test(TokenType::Pipe, "|", 1.5, 0.0), None);
+        assert_eq!(run_bitwise_test(TokenType::LessLess, "<<", 1.0, 60.0), None);
+    }
+
+    #[test]
+    fn test_large_and_negative_shift_counts() {
+        let n = |n| Some(Literal::Number(n));
+        assert_eq!(
+            run_bitwise_test(TokenType::GreaterGreater, ">>", -1.0, 100.0),
+            n(-1.0)
+        );
+        assert_eq!(
+            run_bitwise_test(TokenType::GreaterGreater, ">>", 8.0, 64.0),
+            n(0.0)
+        );
+        assert_eq!(
+            run_bitwise_test(TokenType::LessLess, "<<", 0.0, 64.0),
+            n(0.0)
+        );
+        assert_eq!(run_bitwise_test(TokenType::LessLess, "<<", 1.0, 64.0), None);
+        assert_eq!(run_bitwise_test(TokenType::LessLess, "<<", 1.0, -1.0), None);
+        assert_eq!(
+            run_bitwise_test(TokenType::GreaterGreater, ">>", 1.0, -1.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bitwise_not() {
+        let terp = Interpreter::new();
+        let unary_expr = UnaryExpr {
+            operator: Token::new(TokenType::Tilde, "~".to_string(), None, 123, 1),
+            right: make_literal(Literal::Number(5.0)),
+        };
+        assert_eq!(
+            terp.visit_unary_expr(&unary_expr).ok(),
+            Some(Literal::Number(-6.0))
+        );
+    }
+
+    fn make_while(body: Stmt, label: Option<&str>) -> Stmt {
+        Stmt::While(WhileStmt {
+            condition: *make_literal(Literal::Bool(true)),
+            body: Box::new(body),
+            increment: None,
+            label: label.map(|l| Token::new(TokenType::Identifier, l.to_string(), None, 123, 1)),
+        })
+    }
+
+    #[test]
+    fn test_labeled_break_exits_outer_loop() {
+        let terp = Interpreter::new();
+        let break_stmt = Stmt::Break(BreakStmt {
+            token: Token::new(TokenType::Break, "break".to_string(), None, 123, 1),
+            label: Some(Token::new(
+                TokenType::Identifier,
+                "outer".to_string(),
+                None,
+                123,
+                1,
+            )),
+        });
+        // outer: while (true) { while (true) { break outer; } }
+        let outer = make_while(make_while(break_stmt, None), Some("outer"));
+        assert!(terp.execute(&outer).is_ok());
+        assert_eq!(*terp.nest.borrow(), 0);
+    }
+
+    #[test]
+    fn test_do_while_runs_body_once() {
+        let terp = Interpreter::new();
+        let name = Token::new(TokenType::Identifier, "count".to_string(), None, 123, 1);
+        terp.environment
+            .borrow()
+            .borrow_mut()
+            .define("count", Literal::Number(0.0));
+        // do count = count + 1; while (false);
+        let increment = Expr::Assign(AssignExpr {
+            name: name.dup(),
+            value: Box::new(Expr::Binary(BinaryExpr {
+                left: Box::new(Expr::Variable(VariableExpr { name: name.dup() })),
+                operator: Token::new(TokenType::Plus, "+".to_string(), None, 123, 1),
+                right: make_literal(Literal::Number(1.0)),
+            })),
+        });
+        let do_stmt = Stmt::Do(DoStmt {
+            body: Box::new(Stmt::Expression(ExpressionStmt {
+                expression: increment,
+            })),
+            condition: *make_literal(Literal::Bool(false)),
+            label: None,
+        });
+        assert!(terp.execute(&do_stmt).is_ok());
+        assert_eq!(
+            terp.environment.borrow().borrow().get(&name).unwrap(),
+            Literal::Number(1.0)
+        );
+    }
+
+    fn make_assert(condition: bool) -> AssertStmt {
+        AssertStmt {
+            keyword: Token::new(TokenType::Assert, "assert".to_string(), None, 123, 1),
+            condition: *make_literal(Literal::Bool(condition)),
+            message: Some(*make_literal_string("boom")),
+            source: condition.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_assert() {
+        let terp = Interpreter::new();
+        assert!(terp.visit_assert_stmt(&make_assert(true)).is_ok());
+        assert!(terp.visit_assert_stmt(&make_assert(false)).is_err());
+    }
+
+    #[test]
+    fn test_disabled_assert() {
+        let mut terp = Interpreter::new();
+        terp.disable_asserts();
+        assert!(terp.visit_assert_stmt(&make_assert(false)).is_ok());
+    }
 }
@@ -0,0 +1,40 @@
+mod token_type;
+
+// mod ast_printer;
+mod callable;
+mod environment;
+mod error;
+mod expr;
+mod interpreter;
+mod literal;
+mod lox;
+mod lox_enum;
+mod lox_set;
+mod native_convert;
+mod native_fs;
+mod native_functions;
+mod native_io;
+mod native_math;
+mod native_process;
+mod native_reflect;
+mod native_regex;
+mod native_set;
+mod native_strings;
+mod native_time;
+mod parser;
+mod regex;
+mod scanner;
+mod stmt;
+mod token;
+mod type_checker;
+
+// The embedding API. Hosts create a `Lox`, configure it through its
+// `Interpreter`, define globals, and `eval` source against it; natives
+// written in Rust implement `LoxCallable`.
+pub use callable::{Callable, LoxCallable};
+pub use error::{ErrorKind, LoxError, LoxResult};
+pub use interpreter::Interpreter;
+pub use literal::Literal;
+pub use lox::Lox;
+pub use native_fs::Access;
+pub use token::Token;
@@ -1,7 +1,12 @@
+use std::cell::RefCell;
 use std::cmp::*;
 use std::fmt;
+use std::rc::Rc;
 
 use crate::callable::*;
+use crate::lox_enum::*;
+use crate::lox_set::*;
+use crate::regex::*;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
@@ -10,7 +15,10 @@ pub enum Literal {
     Bool(bool),
     Number(f64),
     String(String),
-    ArithmeticError,
+    Enum(Rc<LoxEnum>),
+    EnumMember(LoxEnumMember),
+    Regex(Rc<Regex>),
+    Set(Rc<RefCell<LoxSet>>),
 }
 
 impl fmt::Display for Literal {
@@ -21,7 +29,10 @@ impl fmt::Display for Literal {
             Literal::Bool(b) => write!(f, "{}", b),
             Literal::Number(n) => write!(f, "{}", n),
             Literal::String(s) => write!(f, "{}", s),
-            Literal::ArithmeticError => panic!("Should not be trying to print ArithmeticError"),
+            Literal::Enum(e) => write!(f, "{}", e),
+            Literal::EnumMember(m) => write!(f, "{}", m),
+            Literal::Regex(r) => write!(f, "<regex {:?}>", r),
+            Literal::Set(s) => write!(f, "{}", s.borrow()),
         }
     }
 }
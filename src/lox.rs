@@ -0,0 +1,133 @@
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::parser::*;
+use crate::scanner::*;
+use crate::stmt::*;
+use crate::type_checker::*;
+
+// An interpreter session. Globals defined by one evaluation stay visible
+// to the next, so a `Lox` can be kept around and fed source piece by piece.
+pub struct Lox {
+    interpreter: Interpreter,
+}
+
+impl Default for Lox {
+    fn default() -> Self {
+        Lox::new()
+    }
+}
+
+impl Lox {
+    pub fn new() -> Lox {
+        Lox {
+            interpreter: Interpreter::new(),
+        }
+    }
+
+    // For configuring the session: asserts, file system access, script
+    // arguments, and whether scripts may exit the process or read stdin.
+    pub fn interpreter_mut(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+
+    pub fn define(&self, name: &str, value: Literal) {
+        self.interpreter.define_global(name, value);
+    }
+
+    // Runs `source` and returns the value of its last statement if that is
+    // an expression statement, or nil. Nothing is printed to stderr; every
+    // error reported along the way is returned instead. Source with syntax
+    // errors does not run at all.
+    pub fn eval(&self, source: &str) -> Result<Literal, Vec<LoxError>> {
+        let statements = Self::parse(source)?;
+        let (result, errors) = capture_errors(|| self.interpreter.run(&statements));
+        result.map_err(|_| errors)
+    }
+
+    // Type-checks `source` without running it.
+    pub fn check(&self, source: &str) -> Result<(), Vec<LoxError>> {
+        let statements = Self::parse(source)?;
+        let (_, errors) = capture_errors(|| TypeChecker::new(&self.interpreter).check(&statements));
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn parse(source: &str) -> Result<Vec<Stmt>, Vec<LoxError>> {
+        let (statements, errors) = capture_errors(|| {
+            let mut scanner = Scanner::new(source.to_string());
+            let tokens = scanner.scan_tokens().ok()?;
+            let mut parser = Parser::new(tokens, source);
+            let statements = parser.parse().ok()?;
+            parser.success().then_some(statements)
+        });
+        match statements {
+            Some(statements) if errors.is_empty() => Ok(statements),
+            _ => Err(errors),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_to_the_last_expression() {
+        let lox = Lox::new();
+        assert_eq!(lox.eval("1 + 2;"), Ok(Literal::Number(3.0)));
+        assert_eq!(lox.eval("var a = 1;"), Ok(Literal::Nil));
+        assert_eq!(lox.eval(""), Ok(Literal::Nil));
+    }
+
+    #[test]
+    fn keeps_globals_between_evaluations() {
+        let lox = Lox::new();
+        lox.define("greeting", Literal::String("hi".to_string()));
+        assert!(lox.eval("var n = len(greeting);").is_ok());
+        assert_eq!(lox.eval("n * 2;"), Ok(Literal::Number(4.0)));
+    }
+
+    #[test]
+    fn returns_structured_errors() {
+        let lox = Lox::new();
+        let errors = lox.eval("print 1;\nprint nope;").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ErrorKind::Runtime);
+        assert_eq!((errors[0].line, errors[0].column), (2, 7));
+        assert_eq!(errors[0].lexeme.as_deref(), Some("nope"));
+
+        let errors = lox.eval("var = 1;").unwrap_err();
+        assert_eq!(errors[0].kind, ErrorKind::Parse);
+        assert_eq!(errors[0].to_string(), "line 1:5 at '=' Expect variable name.");
+
+        let errors = lox.eval("\"open").unwrap_err();
+        assert_eq!(errors[0].kind, ErrorKind::Scan);
+    }
+
+    #[test]
+    fn process_and_stdin_natives_are_opt_in() {
+        let mut lox = Lox::new();
+        let undefined = |result: Result<Literal, Vec<LoxError>>| {
+            result.unwrap_err()[0].message.starts_with("Undefined variable")
+        };
+        assert!(undefined(lox.eval("exit;")));
+        assert!(undefined(lox.eval("read_line;")));
+        lox.interpreter_mut().allow_process();
+        lox.interpreter_mut().allow_stdin();
+        assert!(lox.eval("exit;").is_ok());
+        assert!(lox.eval("read_line;").is_ok());
+    }
+
+    #[test]
+    fn checks_types_without_running() {
+        let lox = Lox::new();
+        assert_eq!(lox.check("var x: Number = 1;"), Ok(()));
+        let errors = lox.check("var x: Number = \"one\";").unwrap_err();
+        assert_eq!(errors[0].kind, ErrorKind::Type);
+        assert_eq!(lox.eval("x;").unwrap_err()[0].kind, ErrorKind::Runtime);
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub struct LoxEnum {
+    pub name: String,
+    pub members: Vec<String>,
+}
+
+// Every `enum` declaration is its own type, even if another one has the
+// same name and members.
+impl PartialEq for LoxEnum {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl LoxEnum {
+    pub fn member(enumeration: &Rc<LoxEnum>, name: &str) -> Option<LoxEnumMember> {
+        enumeration
+            .members
+            .iter()
+            .position(|m| m == name)
+            .map(|ordinal| LoxEnumMember {
+                enumeration: Rc::clone(enumeration),
+                ordinal,
+            })
+    }
+}
+
+impl fmt::Display for LoxEnum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<enum {}>", self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoxEnumMember {
+    pub enumeration: Rc<LoxEnum>,
+    pub ordinal: usize,
+}
+
+impl LoxEnumMember {
+    pub fn name(&self) -> &str {
+        &self.enumeration.members[self.ordinal]
+    }
+}
+
+impl fmt::Display for LoxEnumMember {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.enumeration.name, self.name())
+    }
+}
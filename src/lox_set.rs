@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::literal::*;
+
+// The identity of a value used as a set element, and later as a map key.
+// Two values get the same key exactly when they are equal, so only values
+// with a stable notion of equality are hashable: nil, booleans, numbers
+// other than NaN, strings, and enums and their members.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HashKey {
+    Nil,
+    Bool(bool),
+    // bit pattern, with -0 folded into 0
+    Number(u64),
+    String(String),
+    // enums compare by identity, so they hash by address
+    Enum(usize),
+    EnumMember(usize, usize),
+}
+
+impl HashKey {
+    pub fn of(value: &Literal) -> Option<HashKey> {
+        match value {
+            Literal::Nil => Some(HashKey::Nil),
+            Literal::Bool(b) => Some(HashKey::Bool(*b)),
+            Literal::Number(n) if n.is_nan() => None,
+            Literal::Number(n) => Some(HashKey::Number((n + 0.0).to_bits())),
+            Literal::String(s) => Some(HashKey::String(s.clone())),
+            Literal::Enum(enumeration) => Some(HashKey::Enum(Rc::as_ptr(enumeration) as usize)),
+            Literal::EnumMember(member) => Some(HashKey::EnumMember(
+                Rc::as_ptr(&member.enumeration) as usize,
+                member.ordinal,
+            )),
+            _ => None,
+        }
+    }
+}
+
+// A set that remembers the order its elements were first added in.
+#[derive(Debug, Default, Clone)]
+pub struct LoxSet {
+    values: Vec<Literal>,
+    positions: HashMap<HashKey, usize>,
+}
+
+// Sets are mutable and shared, so like enums they are equal only to
+// themselves.
+impl PartialEq for LoxSet {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl LoxSet {
+    pub fn new() -> LoxSet {
+        LoxSet::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    // In insertion order.
+    pub fn values(&self) -> &[Literal] {
+        &self.values
+    }
+
+    pub fn contains(&self, key: &HashKey) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    // Returns false if the value was already present.
+    pub fn add(&mut self, key: HashKey, value: Literal) -> bool {
+        if self.positions.contains_key(&key) {
+            return false;
+        }
+        self.positions.insert(key, self.values.len());
+        self.values.push(value);
+        true
+    }
+
+    // Returns false if the value was not present.
+    pub fn remove(&mut self, key: &HashKey) -> bool {
+        let Some(position) = self.positions.remove(key) else {
+            return false;
+        };
+        self.values.remove(position);
+        for later in self.positions.values_mut() {
+            if *later > position {
+                *later -= 1;
+            }
+        }
+        true
+    }
+
+    // Elements of `self` for which `keep` holds, in `self`'s order.
+    pub fn filter(&self, keep: impl Fn(&HashKey) -> bool) -> LoxSet {
+        let mut result = LoxSet::new();
+        for value in &self.values {
+            let key = HashKey::of(value).unwrap();
+            if keep(&key) {
+                result.add(key, value.clone());
+            }
+        }
+        result
+    }
+}
+
+impl fmt::Display for LoxSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{")?;
+        for (index, value) in self.values.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", value)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(set: &mut LoxSet, value: Literal) -> bool {
+        set.add(HashKey::of(&value).unwrap(), value)
+    }
+
+    #[test]
+    fn keys_follow_equality() {
+        assert_eq!(
+            HashKey::of(&Literal::Number(0.0)),
+            HashKey::of(&Literal::Number(-0.0))
+        );
+        assert_ne!(
+            HashKey::of(&Literal::Number(1.0)),
+            HashKey::of(&Literal::String("1".to_string()))
+        );
+        assert_eq!(HashKey::of(&Literal::Number(f64::NAN)), None);
+    }
+
+    #[test]
+    fn keeps_insertion_order_across_removals() {
+        let mut set = LoxSet::new();
+        for n in [3.0, 1.0, 2.0, 1.0] {
+            add(&mut set, Literal::Number(n));
+        }
+        assert_eq!(set.len(), 3);
+        assert!(set.remove(&HashKey::Number(3.0f64.to_bits())));
+        assert!(!set.remove(&HashKey::Number(3.0f64.to_bits())));
+        add(&mut set, Literal::Number(3.0));
+        assert_eq!(set.to_string(), "{1, 2, 3}");
+        assert!(set.contains(&HashKey::Number(2.0f64.to_bits())));
+    }
+}
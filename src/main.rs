@@ -1,27 +1,9 @@
-mod token_type;
-
-// mod ast_printer;
-mod callable;
-mod environment;
-mod error;
-mod expr;
-mod interpreter;
-mod native_functions;
-use interpreter::*;
-mod literal;
-mod scanner;
-mod stmt;
-mod token;
-
-mod parser;
-use crate::parser::*;
-
-use error::*;
-use scanner::*;
+use lox_ast::{Access, ErrorKind, Lox, LoxError};
 use std::{
     env::args,
-    io::{self, stdout, BufRead, Write},
+    io::{self, stdout, Write},
 };
+
 fn main() {
     // // test pretty printing
     // let expression = Expr::Binary(BinaryExpr {
@@ -52,70 +34,100 @@ fn main() {
     // println!("{}", printer.print(&expression).unwrap());
     // return;
 
-    let args: Vec<String> = args().collect();
-    let lox = Lox::new();
-    match args.len() {
-        1 => lox.run_prompt(),
-        2 => lox.run_file(&args[1]).expect("Could not run file"),
+    let mut lox = Lox::new();
+    lox.interpreter_mut().allow_process();
+    lox.interpreter_mut().allow_stdin();
+    let mut scripts: Vec<String> = Vec::new();
+    // everything after the script's path belongs to the script
+    let mut arguments = args().skip(1);
+    for arg in arguments.by_ref() {
+        match arg.as_str() {
+            "--disable-asserts" => lox.interpreter_mut().disable_asserts(),
+            _ if arg.starts_with("--allow-read=") => {
+                lox.interpreter_mut().allow_fs(Access::Read, &arg["--allow-read=".len()..])
+            }
+            _ if arg.starts_with("--allow-write=") => {
+                lox.interpreter_mut().allow_fs(Access::Write, &arg["--allow-write=".len()..])
+            }
+            _ => {
+                let is_command = arg == "check" && scripts.is_empty();
+                scripts.push(arg);
+                if !is_command {
+                    break;
+                }
+            }
+        }
+    }
+    let script_args: Vec<String> = arguments.collect();
+
+    match scripts.len() {
+        0 => run_prompt(&lox),
+        1 if scripts[0] != "check" => {
+            lox.interpreter_mut().set_script_args(script_args);
+            run_file(&lox, &scripts[0]).expect("Could not run file")
+        }
+        2 if scripts[0] == "check" && script_args.is_empty() => {
+            check_file(&lox, &scripts[1]).expect("Could not check file")
+        }
         _ => {
-            println!("Usage: lox-ast [script]");
+            println!("Usage: lox-ast [--disable-asserts] [--allow-read=PATH] [--allow-write=PATH] [script [args...]]");
+            println!("       lox-ast check script");
             std::process::exit(64);
         }
     }
 }
 
-struct Lox {
-    interpreter: Interpreter,
+// Exit codes follow sysexits.h, as in clox: 65 for errors in the source,
+// 70 for errors while running it.
+fn exit_code(errors: &[LoxError]) -> i32 {
+    match errors.last().map(|e| e.kind) {
+        Some(ErrorKind::Scan | ErrorKind::Parse | ErrorKind::Type) => 65,
+        _ => 70,
+    }
 }
 
-impl Lox {
-    pub fn new() -> Lox {
-        Lox {
-            interpreter: Interpreter::new(),
-        }
+fn report(errors: &[LoxError]) {
+    for error in errors {
+        eprintln!("{}", error);
     }
+}
 
-    fn run_file(&self, path: &str) -> io::Result<()> {
-        let buf = std::fs::read_to_string(path)?;
-        if self.run(buf).is_err() {
-            // Ignore: error was already reported
-            std::process::exit(65);
-        }
-        Ok(())
+fn run_file(lox: &Lox, path: &str) -> io::Result<()> {
+    let buf = std::fs::read_to_string(path)?;
+    if let Err(errors) = lox.eval(&buf) {
+        report(&errors);
+        std::process::exit(exit_code(&errors));
     }
-    fn run_prompt(&self) {
-        let stdin = io::stdin();
-        print!("> ");
-        let _ = stdout().flush();
+    Ok(())
+}
 
-        for line in stdin.lock().lines() {
-            if let Ok(line) = line {
-                if line.is_empty() {
-                    break;
-                }
-                match self.run(line) {
-                    Ok(_) => {}
-                    Err(_) => {
-                        // ignore, already reported
-                    }
-                }
-            } else {
-                break;
-            }
-            print!("> ");
-            let _ = stdout().flush();
-        }
+// Type-checks a script without running it.
+fn check_file(lox: &Lox, path: &str) -> io::Result<()> {
+    let buf = std::fs::read_to_string(path)?;
+    if let Err(errors) = lox.check(&buf) {
+        report(&errors);
+        std::process::exit(65);
     }
+    Ok(())
+}
 
-    fn run(&self, source: String) -> Result<(), LoxResult> {
-        let mut scanner = Scanner::new(source);
-        let tokens = scanner.scan_tokens()?;
+fn run_prompt(lox: &Lox) {
+    loop {
+        print!("> ");
+        let _ = stdout().flush();
 
-        let mut parser = Parser::new(tokens);
-        let statements = parser.parse()?;
-        if parser.success() {
-            self.interpreter.interpret(&statements);
+        // stdin is locked only while reading, so scripts can read it too
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line = line.trim_end_matches(['\n', '\r']);
+        if line.is_empty() {
+            break;
+        }
+        if let Err(errors) = lox.eval(line) {
+            report(&errors);
         }
-        Ok(())
     }
 }
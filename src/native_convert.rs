@@ -0,0 +1,129 @@
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::native_functions::*;
+use crate::token::*;
+
+pub static CONVERT_NATIVES: [NativeFunction; 4] = [
+    NativeFunction {
+        name: "num",
+        arity: 1,
+        variadic: false,
+        func: num,
+    },
+    NativeFunction {
+        name: "int",
+        arity: 1,
+        variadic: false,
+        func: int,
+    },
+    NativeFunction {
+        name: "str",
+        arity: 1,
+        variadic: false,
+        func: str,
+    },
+    NativeFunction {
+        name: "bool",
+        arity: 1,
+        variadic: false,
+        func: bool,
+    },
+];
+
+// Parses decimal notation such as "42", "-1.5", ".5" or "2e10", ignoring
+// surrounding whitespace. Rust's other spellings ("inf", "NaN") are not
+// numbers to Lox.
+fn parse_number(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+    if !digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn to_number(paren: &Token, name: &str, args: &[Literal]) -> Result<Option<f64>, LoxResult> {
+    match &args[0] {
+        Literal::Number(n) => Ok(Some(*n)),
+        Literal::String(s) => Ok(parse_number(s)),
+        _ => Err(LoxResult::runtime_error(
+            paren,
+            &format!("Argument 1 of '{}' must be a number or a string.", name),
+        )),
+    }
+}
+
+// Strings that are not numbers give nil, so bad input can be handled
+// without stopping the script.
+fn num(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    Ok(to_number(paren, "num", args)?.map_or(Literal::Nil, Literal::Number))
+}
+
+// Like `num`, but drops any fractional part, rounding toward zero.
+fn int(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    match to_number(paren, "int", args)? {
+        Some(n) if !n.is_finite() => Err(LoxResult::runtime_error(
+            paren,
+            &format!("Cannot convert {} to an integer.", n),
+        )),
+        Some(n) => Ok(Literal::Number(n.trunc())),
+        None => Ok(Literal::Nil),
+    }
+}
+
+// Formats values exactly as `print` does.
+fn str(_terp: &Interpreter, args: &[Literal], _paren: &Token) -> Result<Literal, LoxResult> {
+    Ok(Literal::String(args[0].to_string()))
+}
+
+fn bool(terp: &Interpreter, args: &[Literal], _paren: &Token) -> Result<Literal, LoxResult> {
+    Ok(Literal::Bool(terp.is_truthy(&args[0])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(call(num, &[string(" 42\n")]), Some(Literal::Number(42.0)));
+        assert_eq!(call(num, &[string("-1.5")]), Some(Literal::Number(-1.5)));
+        assert_eq!(call(num, &[string("2e3")]), Some(Literal::Number(2000.0)));
+        assert_eq!(
+            call(num, &[Literal::Number(7.0)]),
+            Some(Literal::Number(7.0))
+        );
+    }
+
+    #[test]
+    fn bad_input_is_nil_or_error() {
+        assert_eq!(call(num, &[string("12abc")]), Some(Literal::Nil));
+        assert_eq!(call(num, &[string("")]), Some(Literal::Nil));
+        assert_eq!(call(num, &[string("inf")]), Some(Literal::Nil));
+        assert_eq!(call(num, &[string("NaN")]), Some(Literal::Nil));
+        assert_eq!(call(num, &[Literal::Bool(true)]), None);
+        assert_eq!(call(int, &[Literal::Number(f64::INFINITY)]), None);
+    }
+
+    #[test]
+    fn int_truncates_toward_zero() {
+        assert_eq!(
+            call(int, &[Literal::Number(2.9)]),
+            Some(Literal::Number(2.0))
+        );
+        assert_eq!(call(int, &[string("-2.9")]), Some(Literal::Number(-2.0)));
+    }
+
+    #[test]
+    fn str_and_bool() {
+        assert_eq!(call(str, &[Literal::Number(1.5)]), Some(string("1.5")));
+        assert_eq!(call(str, &[Literal::Nil]), Some(string("nil")));
+        assert_eq!(
+            call(bool, &[Literal::Number(0.0)]),
+            Some(Literal::Bool(true))
+        );
+        assert_eq!(call(bool, &[Literal::Nil]), Some(Literal::Bool(false)));
+    }
+}
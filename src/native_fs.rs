@@ -0,0 +1,293 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::native_functions::*;
+use crate::token::*;
+
+pub static FS_NATIVES: [NativeFunction; 6] = [
+    NativeFunction {
+        name: "read_file",
+        arity: 1,
+        variadic: false,
+        func: read_file,
+    },
+    NativeFunction {
+        name: "write_file",
+        arity: 2,
+        variadic: false,
+        func: write_file,
+    },
+    NativeFunction {
+        name: "append_file",
+        arity: 2,
+        variadic: false,
+        func: append_file,
+    },
+    NativeFunction {
+        name: "exists",
+        arity: 1,
+        variadic: false,
+        func: exists,
+    },
+    NativeFunction {
+        name: "remove",
+        arity: 1,
+        variadic: false,
+        func: remove,
+    },
+    NativeFunction {
+        name: "mkdir",
+        arity: 1,
+        variadic: false,
+        func: mkdir,
+    },
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+// Path prefixes scripts may touch. Everything is denied until a prefix is
+// allowed. Prefixes and paths are compared as the OS would resolve them,
+// following symlinks, so a link inside an allowed directory cannot reach
+// outside it.
+#[derive(Default)]
+pub struct FsPermissions {
+    read: Vec<PathBuf>,
+    write: Vec<PathBuf>,
+}
+
+impl FsPermissions {
+    pub fn allow(&mut self, access: Access, prefix: &str) {
+        let prefix = Path::new(prefix);
+        let prefix = resolve(prefix).unwrap_or_else(|| absolute(prefix));
+        match access {
+            Access::Read => self.read.push(prefix),
+            Access::Write => self.write.push(prefix),
+        }
+    }
+
+    pub fn allows(&self, access: Access, path: &Path) -> bool {
+        let prefixes = match access {
+            Access::Read => &self.read,
+            Access::Write => &self.write,
+        };
+        let Some(path) = resolve(path) else {
+            return false;
+        };
+        prefixes.iter().any(|prefix| path.starts_with(prefix))
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    match std::env::current_dir() {
+        Ok(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+// Resolves `path` a component at a time, as the OS would when creating
+// it: each prefix that exists is canonicalized, so a `..` that walks back
+// out of missing directories still follows any symlink it reaches. `None`
+// if a prefix is a dangling symlink, since writing through it would create
+// its target wherever that is.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let mut resolved = PathBuf::new();
+    for component in absolute(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            _ => {
+                resolved.push(component);
+                if resolved.symlink_metadata().is_ok() {
+                    resolved = resolved.canonicalize().ok()?;
+                }
+            }
+        }
+    }
+    Some(resolved)
+}
+
+// Returns the first argument as a path, if the script may access it.
+fn path_arg<'a>(
+    terp: &Interpreter,
+    paren: &Token,
+    name: &str,
+    args: &'a [Literal],
+    access: Access,
+) -> Result<&'a Path, LoxResult> {
+    let path = Path::new(string_arg(paren, name, args, 0)?);
+    if terp.fs_permissions().allows(access, path) {
+        return Ok(path);
+    }
+
+    let flag = match access {
+        Access::Read => "--allow-read",
+        Access::Write => "--allow-write",
+    };
+    Err(LoxResult::runtime_error(
+        paren,
+        &format!(
+            "'{}' may not access '{}'; allow it with {}.",
+            name,
+            path.display(),
+            flag
+        ),
+    ))
+}
+
+// Like every runtime error, permission and I/O failures are reported as
+// soon as they are raised; scripts cannot catch them.
+fn io_error(paren: &Token, name: &str, path: &Path, error: io::Error) -> LoxResult {
+    LoxResult::runtime_error(
+        paren,
+        &format!("'{}' failed on '{}': {}.", name, path.display(), error),
+    )
+}
+
+fn read_file(terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let path = path_arg(terp, paren, "read_file", args, Access::Read)?;
+    fs::read_to_string(path)
+        .map(Literal::String)
+        .map_err(|e| io_error(paren, "read_file", path, e))
+}
+
+fn write_file(terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let path = path_arg(terp, paren, "write_file", args, Access::Write)?;
+    let contents = string_arg(paren, "write_file", args, 1)?;
+    fs::write(path, contents)
+        .map(|_| Literal::Nil)
+        .map_err(|e| io_error(paren, "write_file", path, e))
+}
+
+fn append_file(terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let path = path_arg(terp, paren, "append_file", args, Access::Write)?;
+    let contents = string_arg(paren, "append_file", args, 1)?;
+    fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map(|_| Literal::Nil)
+        .map_err(|e| io_error(paren, "append_file", path, e))
+}
+
+fn exists(terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let path = path_arg(terp, paren, "exists", args, Access::Read)?;
+    Ok(Literal::Bool(path.exists()))
+}
+
+// Removes a file or an empty directory.
+fn remove(terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let path = path_arg(terp, paren, "remove", args, Access::Write)?;
+    let result = if path.is_dir() {
+        fs::remove_dir(path)
+    } else {
+        fs::remove_file(path)
+    };
+    result
+        .map(|_| Literal::Nil)
+        .map_err(|e| io_error(paren, "remove", path, e))
+}
+
+// Creates missing parent directories too.
+fn mkdir(terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let path = path_arg(terp, paren, "mkdir", args, Access::Write)?;
+    fs::create_dir_all(path)
+        .map(|_| Literal::Nil)
+        .map_err(|e| io_error(paren, "mkdir", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+
+    #[test]
+    fn prefixes_match_whole_components() {
+        let mut permissions = FsPermissions::default();
+        permissions.allow(Access::Read, "./data");
+        assert!(permissions.allows(Access::Read, Path::new("data/in.txt")));
+        assert!(permissions.allows(Access::Read, Path::new("./data")));
+        assert!(!permissions.allows(Access::Read, Path::new("database")));
+        assert!(!permissions.allows(Access::Read, Path::new("data/../secret")));
+        assert!(!permissions.allows(Access::Write, Path::new("data/in.txt")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_cannot_leave_allowed_prefixes() {
+        use std::os::unix::fs::symlink;
+
+        let base = std::env::temp_dir().join(format!("lox-fs-links-{}", std::process::id()));
+        let data = base.join("data");
+        let outside = base.join("outside");
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(&data).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret.txt"), "secret").unwrap();
+        fs::write(data.join("in.txt"), "in").unwrap();
+        symlink(&outside, data.join("out")).unwrap();
+        symlink(outside.join("missing"), data.join("dangling")).unwrap();
+        symlink(data.join("in.txt"), outside.join("back")).unwrap();
+
+        let mut permissions = FsPermissions::default();
+        permissions.allow(Access::Read, data.to_str().unwrap());
+        permissions.allow(Access::Write, data.to_str().unwrap());
+        assert!(!permissions.allows(Access::Read, &data.join("out/secret.txt")));
+        assert!(!permissions.allows(Access::Write, &data.join("out/new/file.txt")));
+        assert!(!permissions.allows(Access::Write, &data.join("dangling")));
+        assert!(!permissions.allows(Access::Read, &data.join("out/../secret.txt")));
+        assert!(permissions.allows(Access::Read, &data.join("out/../data/in.txt")));
+        assert!(permissions.allows(Access::Write, &data.join("new/../in.txt")));
+        assert!(!permissions.allows(Access::Write, &data.join("x/../out/evil")));
+        assert!(permissions.allows(Access::Read, &outside.join("back")));
+
+        // allowing a link allows what it points to
+        let mut permissions = FsPermissions::default();
+        permissions.allow(Access::Read, data.join("out").to_str().unwrap());
+        assert!(permissions.allows(Access::Read, &outside.join("secret.txt")));
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn denied_by_default() {
+        let terp = Interpreter::new();
+        assert!(read_file(&terp, &[string("Cargo.toml")], &paren()).is_err());
+        assert!(exists(&terp, &[string("Cargo.toml")], &paren()).is_err());
+    }
+
+    #[test]
+    fn reads_and_writes_allowed_paths() {
+        let dir = std::env::temp_dir().join(format!("lox-fs-{}", std::process::id()));
+        let dir_name = dir.to_str().unwrap();
+        let file = dir.join("out.txt");
+        let file_name = string(file.to_str().unwrap());
+        let path = [file_name.clone()];
+
+        let mut terp = Interpreter::new();
+        terp.allow_fs(Access::Read, dir_name);
+        terp.allow_fs(Access::Write, dir_name);
+
+        assert!(mkdir(&terp, &[string(dir_name)], &paren()).is_ok());
+        assert!(write_file(&terp, &[file_name.clone(), string("a")], &paren()).is_ok());
+        assert!(append_file(&terp, &[file_name, string("b")], &paren()).is_ok());
+        assert_eq!(read_file(&terp, &path, &paren()).ok(), Some(string("ab")));
+        assert!(remove(&terp, &path, &paren()).is_ok());
+        assert_eq!(
+            exists(&terp, &path, &paren()).ok(),
+            Some(Literal::Bool(false))
+        );
+        assert!(read_file(&terp, &path, &paren()).is_err());
+        assert!(remove(&terp, &[string(dir_name)], &paren()).is_ok());
+    }
+}
@@ -1,24 +1,167 @@
-use std::time::SystemTime;
+use std::rc::Rc;
 
 use crate::callable::*;
+use crate::environment::*;
 use crate::error::*;
 use crate::interpreter::*;
 use crate::literal::*;
+use crate::native_convert::*;
+use crate::native_fs::*;
+use crate::native_math::*;
+use crate::native_reflect::*;
+use crate::native_regex::*;
+use crate::native_set::*;
+use crate::native_strings::*;
+use crate::native_time::*;
+use crate::token::*;
 
-pub struct NativeClock;
+// The natives every interpreter has. Those reaching the host process or
+// its stdin are defined by `Interpreter::allow_process` and `allow_stdin`.
+pub fn define_natives(globals: &mut Environment) {
+    for native in REFLECT_NATIVES
+        .iter()
+        .chain(&STRING_NATIVES)
+        .chain(&MATH_NATIVES)
+        .chain(&FS_NATIVES)
+        .chain(&CONVERT_NATIVES)
+        .chain(&TIME_NATIVES)
+        .chain(&REGEX_NATIVES)
+        .chain(&SET_NATIVES)
+    {
+        native.define(globals);
+    }
+    define_math_globals(globals);
+}
 
-impl LoxCallable for NativeClock {
-    fn call(&self, _terp: &Interpreter, _args: Vec<Literal>) -> Result<Literal, LoxResult> {
-        match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => Ok(Literal::Number(n.as_millis() as f64)),
-            Err(e) => Err(LoxResult::system_error(&format!(
-                "Clock returned invalid duration: {:?}",
-                e.duration()
-            ))),
-        }
+// A native backed by a plain function, for natives that need no state of
+// their own.
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub variadic: bool,
+    pub func: NativeFn,
+}
+
+pub type NativeFn = fn(&Interpreter, &[Literal], &Token) -> Result<Literal, LoxResult>;
+
+impl NativeFunction {
+    pub fn define(&'static self, globals: &mut Environment) {
+        globals.define(
+            self.name,
+            Literal::Func(Callable {
+                func: Rc::new(self),
+            }),
+        );
+    }
+}
+
+impl LoxCallable for &'static NativeFunction {
+    fn call(
+        &self,
+        terp: &Interpreter,
+        args: Vec<Literal>,
+        paren: &Token,
+    ) -> Result<Literal, LoxResult> {
+        (self.func)(terp, &args, paren)
     }
 
     fn arity(&self) -> usize {
-        0
+        self.arity
+    }
+
+    fn is_variadic(&self) -> bool {
+        self.variadic
+    }
+}
+
+// Checks the type of argument `index` (0-based) of native `name`, reporting
+// the 1-based argument position on mismatch.
+pub fn string_arg<'a>(
+    paren: &Token,
+    name: &str,
+    arguments: &'a [Literal],
+    index: usize,
+) -> Result<&'a str, LoxResult> {
+    match &arguments[index] {
+        Literal::String(s) => Ok(s),
+        _ => Err(LoxResult::runtime_error(
+            paren,
+            &format!("Argument {} of '{}' must be a string.", index + 1, name),
+        )),
+    }
+}
+
+pub fn number_arg(
+    paren: &Token,
+    name: &str,
+    arguments: &[Literal],
+    index: usize,
+) -> Result<f64, LoxResult> {
+    match &arguments[index] {
+        Literal::Number(n) => Ok(*n),
+        _ => Err(LoxResult::runtime_error(
+            paren,
+            &format!("Argument {} of '{}' must be a number.", index + 1, name),
+        )),
+    }
+}
+
+// Integers an f64 represents exactly, such as timestamps and seeds.
+pub fn integer_arg(
+    paren: &Token,
+    name: &str,
+    arguments: &[Literal],
+    index: usize,
+) -> Result<i64, LoxResult> {
+    let n = number_arg(paren, name, arguments, index)?;
+    if n.fract() == 0.0 && n.abs() <= 9_007_199_254_740_991.0 {
+        Ok(n as i64)
+    } else {
+        Err(LoxResult::runtime_error(
+            paren,
+            &format!("Argument {} of '{}' must be an integer.", index + 1, name),
+        ))
+    }
+}
+
+// For counts and character positions.
+pub fn index_arg(
+    paren: &Token,
+    name: &str,
+    arguments: &[Literal],
+    index: usize,
+) -> Result<usize, LoxResult> {
+    match &arguments[index] {
+        Literal::Number(n) if n.fract() == 0.0 && *n >= 0.0 && *n <= usize::MAX as f64 => {
+            Ok(*n as usize)
+        }
+        _ => Err(LoxResult::runtime_error(
+            paren,
+            &format!(
+                "Argument {} of '{}' must be a non-negative integer.",
+                index + 1,
+                name
+            ),
+        )),
+    }
+}
+
+// Shared by the natives' unit tests.
+#[cfg(test)]
+pub mod test_support {
+    use super::*;
+    use crate::token_type::*;
+
+    pub fn paren() -> Token {
+        Token::new(TokenType::RightParen, ")".to_string(), None, 123, 1)
+    }
+
+    // Calls `func` with a fresh interpreter; an error becomes `None`.
+    pub fn call(func: NativeFn, args: &[Literal]) -> Option<Literal> {
+        func(&Interpreter::new(), args, &paren()).ok()
+    }
+
+    pub fn string(s: &str) -> Literal {
+        Literal::String(s.to_string())
     }
 }
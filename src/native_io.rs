@@ -0,0 +1,121 @@
+use std::io::{self, Write};
+
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::native_functions::*;
+use crate::token::*;
+
+pub static IO_NATIVES: [NativeFunction; 3] = [
+    NativeFunction {
+        name: "input",
+        arity: 1,
+        variadic: false,
+        func: input,
+    },
+    NativeFunction {
+        name: "read_line",
+        arity: 0,
+        variadic: false,
+        func: read_line,
+    },
+    NativeFunction {
+        name: "read_all",
+        arity: 0,
+        variadic: false,
+        func: read_all,
+    },
+];
+
+fn io_error(paren: &Token, name: &str, error: io::Error) -> LoxResult {
+    LoxResult::runtime_error(
+        paren,
+        &format!("'{}' could not read stdin: {}.", name, error),
+    )
+}
+
+// Reads one line without its line terminator, or nil at end of input.
+fn next_line(terp: &Interpreter, paren: &Token, name: &str) -> Result<Literal, LoxResult> {
+    let mut line = String::new();
+    match terp.read_stdin(|stdin| stdin.read_line(&mut line)) {
+        Ok(0) => Ok(Literal::Nil),
+        Ok(_) => {
+            if line.ends_with('\n') {
+                line.pop();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+            }
+            Ok(Literal::String(line))
+        }
+        Err(e) => Err(io_error(paren, name, e)),
+    }
+}
+
+// Prints the prompt without a newline, then reads a line.
+fn input(terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let prompt = string_arg(paren, "input", args, 0)?;
+    print!("{}", prompt);
+    let _ = io::stdout().flush();
+    next_line(terp, paren, "input")
+}
+
+fn read_line(terp: &Interpreter, _args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    next_line(terp, paren, "read_line")
+}
+
+// Reads everything left on stdin; "" at end of input.
+fn read_all(terp: &Interpreter, _args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let mut all = String::new();
+    terp.read_stdin(|stdin| stdin.read_to_string(&mut all))
+        .map(|_| Literal::String(all))
+        .map_err(|e| io_error(paren, "read_all", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+    use std::io::Cursor;
+
+    fn reading(input: &str) -> Interpreter {
+        let mut terp = Interpreter::new();
+        terp.set_stdin(Cursor::new(input.to_string()));
+        terp
+    }
+
+    #[test]
+    fn reads_lines_until_end_of_input() {
+        let terp = reading("first\r\nsecond\nlast");
+        let line = || read_line(&terp, &[], &paren()).ok();
+        assert_eq!(line(), Some(string("first")));
+        assert_eq!(line(), Some(string("second")));
+        assert_eq!(line(), Some(string("last")));
+        assert_eq!(line(), Some(Literal::Nil));
+    }
+
+    #[test]
+    fn blank_lines_are_not_end_of_input() {
+        let terp = reading("\n");
+        assert_eq!(read_line(&terp, &[], &paren()).ok(), Some(string("")));
+        assert_eq!(read_line(&terp, &[], &paren()).ok(), Some(Literal::Nil));
+    }
+
+    #[test]
+    fn input_reads_a_line_after_the_prompt() {
+        let terp = reading("yes\n");
+        assert_eq!(
+            input(&terp, &[string("")], &paren()).ok(),
+            Some(string("yes"))
+        );
+        assert!(input(&terp, &[Literal::Nil], &paren()).is_err());
+    }
+
+    #[test]
+    fn read_all_takes_the_rest() {
+        let terp = reading("one\ntwo\n");
+        assert_eq!(read_line(&terp, &[], &paren()).ok(), Some(string("one")));
+        assert_eq!(read_all(&terp, &[], &paren()).ok(), Some(string("two\n")));
+        assert_eq!(read_all(&terp, &[], &paren()).ok(), Some(string("")));
+    }
+}
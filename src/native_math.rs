@@ -0,0 +1,457 @@
+use std::cell::Cell;
+use std::f64::consts;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::callable::*;
+use crate::environment::*;
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::native_functions::*;
+use crate::token::*;
+
+pub static MATH_NATIVES: [NativeFunction; 21] = [
+    NativeFunction {
+        name: "sqrt",
+        arity: 1,
+        variadic: false,
+        func: sqrt,
+    },
+    NativeFunction {
+        name: "pow",
+        arity: 2,
+        variadic: false,
+        func: pow,
+    },
+    NativeFunction {
+        name: "abs",
+        arity: 1,
+        variadic: false,
+        func: abs,
+    },
+    NativeFunction {
+        name: "floor",
+        arity: 1,
+        variadic: false,
+        func: floor,
+    },
+    NativeFunction {
+        name: "ceil",
+        arity: 1,
+        variadic: false,
+        func: ceil,
+    },
+    NativeFunction {
+        name: "round",
+        arity: 1,
+        variadic: false,
+        func: round,
+    },
+    NativeFunction {
+        name: "min",
+        arity: 1,
+        variadic: true,
+        func: min,
+    },
+    NativeFunction {
+        name: "max",
+        arity: 1,
+        variadic: true,
+        func: max,
+    },
+    NativeFunction {
+        name: "sin",
+        arity: 1,
+        variadic: false,
+        func: sin,
+    },
+    NativeFunction {
+        name: "cos",
+        arity: 1,
+        variadic: false,
+        func: cos,
+    },
+    NativeFunction {
+        name: "tan",
+        arity: 1,
+        variadic: false,
+        func: tan,
+    },
+    NativeFunction {
+        name: "asin",
+        arity: 1,
+        variadic: false,
+        func: asin,
+    },
+    NativeFunction {
+        name: "acos",
+        arity: 1,
+        variadic: false,
+        func: acos,
+    },
+    NativeFunction {
+        name: "atan",
+        arity: 1,
+        variadic: false,
+        func: atan,
+    },
+    NativeFunction {
+        name: "atan2",
+        arity: 2,
+        variadic: false,
+        func: atan2,
+    },
+    NativeFunction {
+        name: "exp",
+        arity: 1,
+        variadic: false,
+        func: exp,
+    },
+    NativeFunction {
+        name: "log",
+        arity: 1,
+        variadic: false,
+        func: log,
+    },
+    NativeFunction {
+        name: "log2",
+        arity: 1,
+        variadic: false,
+        func: log2,
+    },
+    NativeFunction {
+        name: "log10",
+        arity: 1,
+        variadic: false,
+        func: log10,
+    },
+    NativeFunction {
+        name: "is_nan",
+        arity: 1,
+        variadic: false,
+        func: is_nan,
+    },
+    NativeFunction {
+        name: "is_finite",
+        arity: 1,
+        variadic: false,
+        func: is_finite,
+    },
+];
+
+// Constants and the natives sharing the random number generator's state.
+// The constants are capitalized so they do not take names scripts use for
+// their own variables.
+pub fn define_math_globals(globals: &mut Environment) {
+    globals.define("PI", Literal::Number(consts::PI));
+    globals.define("E", Literal::Number(consts::E));
+
+    let rng = Rc::new(Rng::from_clock());
+    let natives: [(&str, Rc<dyn LoxCallable>); 3] = [
+        ("random", Rc::new(NativeRandom(Rc::clone(&rng)))),
+        ("random_int", Rc::new(NativeRandomInt(Rc::clone(&rng)))),
+        ("seed", Rc::new(NativeSeed(rng))),
+    ];
+    for (name, func) in natives {
+        globals.define(name, Literal::Func(Callable { func }));
+    }
+}
+
+fn unary(
+    paren: &Token,
+    name: &str,
+    args: &[Literal],
+    f: fn(f64) -> f64,
+) -> Result<Literal, LoxResult> {
+    Ok(Literal::Number(f(number_arg(paren, name, args, 0)?)))
+}
+
+fn sqrt(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "sqrt", args, f64::sqrt)
+}
+
+fn pow(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let base = number_arg(paren, "pow", args, 0)?;
+    let exponent = number_arg(paren, "pow", args, 1)?;
+    Ok(Literal::Number(base.powf(exponent)))
+}
+
+fn abs(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "abs", args, f64::abs)
+}
+
+fn floor(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "floor", args, f64::floor)
+}
+
+fn ceil(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "ceil", args, f64::ceil)
+}
+
+// Halfway cases round away from zero.
+fn round(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "round", args, f64::round)
+}
+
+// min and max take one or more numbers; NaN anywhere makes the result NaN.
+fn min(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    fold(paren, "min", args, |a, b| if b < a { b } else { a })
+}
+
+fn max(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    fold(paren, "max", args, |a, b| if b > a { b } else { a })
+}
+
+fn fold(
+    paren: &Token,
+    name: &str,
+    args: &[Literal],
+    pick: fn(f64, f64) -> f64,
+) -> Result<Literal, LoxResult> {
+    let mut result = number_arg(paren, name, args, 0)?;
+    for index in 1..args.len() {
+        let n = number_arg(paren, name, args, index)?;
+        result = if n.is_nan() || result.is_nan() {
+            f64::NAN
+        } else {
+            pick(result, n)
+        };
+    }
+    Ok(Literal::Number(result))
+}
+
+fn sin(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "sin", args, f64::sin)
+}
+
+fn cos(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "cos", args, f64::cos)
+}
+
+fn tan(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "tan", args, f64::tan)
+}
+
+fn asin(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "asin", args, f64::asin)
+}
+
+fn acos(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "acos", args, f64::acos)
+}
+
+fn atan(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "atan", args, f64::atan)
+}
+
+fn atan2(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let y = number_arg(paren, "atan2", args, 0)?;
+    let x = number_arg(paren, "atan2", args, 1)?;
+    Ok(Literal::Number(y.atan2(x)))
+}
+
+fn exp(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "exp", args, f64::exp)
+}
+
+// Natural logarithm.
+fn log(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "log", args, f64::ln)
+}
+
+fn log2(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "log2", args, f64::log2)
+}
+
+fn log10(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    unary(paren, "log10", args, f64::log10)
+}
+
+fn is_nan(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    Ok(Literal::Bool(
+        number_arg(paren, "is_nan", args, 0)?.is_nan(),
+    ))
+}
+
+fn is_finite(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    Ok(Literal::Bool(
+        number_arg(paren, "is_finite", args, 0)?.is_finite(),
+    ))
+}
+
+// SplitMix64: small, fast and fully determined by its seed, so a script
+// that calls `seed(n)` sees the same sequence on every run and platform.
+pub struct Rng {
+    state: Cell<u64>,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng {
+            state: Cell::new(seed),
+        }
+    }
+
+    fn from_clock() -> Rng {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Rng::new(nanos)
+    }
+
+    fn seed(&self, seed: u64) {
+        self.state.set(seed);
+    }
+
+    fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in [0, 1), using the top 53 bits
+    fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // uniform in [low, high], without modulo bias
+    fn next_in_range(&self, low: i64, high: i64) -> i64 {
+        let span = (high - low) as u64 + 1;
+        let zone = u64::MAX - (u64::MAX % span);
+        loop {
+            let n = self.next_u64();
+            if n < zone {
+                return low + (n % span) as i64;
+            }
+        }
+    }
+}
+
+pub struct NativeRandom(Rc<Rng>);
+
+impl LoxCallable for NativeRandom {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        _args: Vec<Literal>,
+        _paren: &Token,
+    ) -> Result<Literal, LoxResult> {
+        Ok(Literal::Number(self.0.next_f64()))
+    }
+
+    fn arity(&self) -> usize {
+        0
+    }
+}
+
+// random_int(a, b) includes both ends.
+pub struct NativeRandomInt(Rc<Rng>);
+
+impl LoxCallable for NativeRandomInt {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<Literal>,
+        paren: &Token,
+    ) -> Result<Literal, LoxResult> {
+        let low = integer_arg(paren, "random_int", &args, 0)?;
+        let high = integer_arg(paren, "random_int", &args, 1)?;
+        if high < low {
+            return Err(LoxResult::runtime_error(
+                paren,
+                "Argument 2 of 'random_int' must not be less than argument 1.",
+            ));
+        }
+        Ok(Literal::Number(self.0.next_in_range(low, high) as f64))
+    }
+
+    fn arity(&self) -> usize {
+        2
+    }
+}
+
+pub struct NativeSeed(Rc<Rng>);
+
+impl LoxCallable for NativeSeed {
+    fn call(
+        &self,
+        _terp: &Interpreter,
+        args: Vec<Literal>,
+        paren: &Token,
+    ) -> Result<Literal, LoxResult> {
+        let seed = integer_arg(paren, "seed", &args, 0)?;
+        self.0.seed(seed as u64);
+        Ok(Literal::Nil)
+    }
+
+    fn arity(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+
+    fn numbers(ns: &[f64]) -> Vec<Literal> {
+        ns.iter().map(|n| Literal::Number(*n)).collect()
+    }
+
+    #[test]
+    fn min_and_max_are_variadic() {
+        let terp = Interpreter::new();
+        assert_eq!(
+            min(&terp, &numbers(&[3.0, -1.0, 2.0]), &paren()).ok(),
+            Some(Literal::Number(-1.0))
+        );
+        assert_eq!(
+            max(&terp, &numbers(&[3.0]), &paren()).ok(),
+            Some(Literal::Number(3.0))
+        );
+        let args = vec![Literal::Number(1.0), Literal::Nil];
+        assert!(max(&terp, &args, &paren()).is_err());
+    }
+
+    #[test]
+    fn rejects_non_numbers() {
+        let terp = Interpreter::new();
+        assert!(sqrt(&terp, &[Literal::String("4".to_string())], &paren()).is_err());
+    }
+
+    #[test]
+    fn seeded_sequences_repeat() {
+        let first = Rng::new(42);
+        let second = Rng::new(42);
+        for _ in 0..10 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+    }
+
+    #[test]
+    fn random_values_stay_in_range() {
+        let rng = Rng::new(7);
+        for _ in 0..1000 {
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+            let n = rng.next_in_range(-2, 2);
+            assert!((-2..=2).contains(&n));
+        }
+    }
+
+    #[test]
+    fn random_int_checks_bounds() {
+        let terp = Interpreter::new();
+        let random_int = NativeRandomInt(Rc::new(Rng::new(1)));
+        assert!(random_int
+            .call(&terp, numbers(&[5.0, 1.0]), &paren())
+            .is_err());
+        assert!(random_int
+            .call(&terp, numbers(&[0.5, 1.0]), &paren())
+            .is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::{self, Write};
+
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::native_functions::*;
+use crate::token::*;
+
+pub static PROCESS_NATIVES: [NativeFunction; 4] = [
+    NativeFunction {
+        name: "arg_count",
+        arity: 0,
+        variadic: false,
+        func: arg_count,
+    },
+    NativeFunction {
+        name: "arg",
+        arity: 1,
+        variadic: false,
+        func: arg,
+    },
+    NativeFunction {
+        name: "exit",
+        arity: 1,
+        variadic: false,
+        func: exit,
+    },
+    NativeFunction {
+        name: "getenv",
+        arity: 1,
+        variadic: false,
+        func: getenv,
+    },
+];
+
+// Script arguments are those after the script's path on the command line.
+// Until Lox has lists they are read one at a time with `arg(i)`.
+fn arg_count(terp: &Interpreter, _args: &[Literal], _paren: &Token) -> Result<Literal, LoxResult> {
+    Ok(Literal::Number(terp.script_args().len() as f64))
+}
+
+fn arg(terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let index = index_arg(paren, "arg", args, 0)?;
+    match terp.script_args().get(index) {
+        Some(arg) => Ok(Literal::String(arg.clone())),
+        None => Err(LoxResult::runtime_error(
+            paren,
+            "Argument 1 of 'arg' is out of range.",
+        )),
+    }
+}
+
+// Flushes buffered output before terminating the process with `code`.
+fn exit(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let code = integer_arg(paren, "exit", args, 0)?;
+    let Ok(code) = i32::try_from(code) else {
+        return Err(LoxResult::runtime_error(
+            paren,
+            "Argument 1 of 'exit' is out of range.",
+        ));
+    };
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+    std::process::exit(code);
+}
+
+// nil when the variable is unset or not valid Unicode.
+fn getenv(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let name = string_arg(paren, "getenv", args, 0)?;
+    Ok(std::env::var(name).map_or(Literal::Nil, Literal::String))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+
+    #[test]
+    fn reads_script_args() {
+        let mut terp = Interpreter::new();
+        terp.set_script_args(vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(
+            arg_count(&terp, &[], &paren()).ok(),
+            Some(Literal::Number(2.0))
+        );
+        assert_eq!(
+            arg(&terp, &[Literal::Number(1.0)], &paren()).ok(),
+            Some(Literal::String("b".to_string()))
+        );
+        assert!(arg(&terp, &[Literal::Number(2.0)], &paren()).is_err());
+    }
+
+    #[test]
+    fn unset_variables_are_nil() {
+        let terp = Interpreter::new();
+        let name = Literal::String("LOX_TEST_SURELY_UNSET_VARIABLE".to_string());
+        assert_eq!(getenv(&terp, &[name], &paren()).ok(), Some(Literal::Nil));
+    }
+}
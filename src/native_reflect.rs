@@ -0,0 +1,160 @@
+use std::rc::Rc;
+
+use crate::callable::*;
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::lox_enum::*;
+use crate::native_functions::*;
+use crate::token::*;
+use crate::token_type::*;
+
+// `len` also counts the members of an enum; see native_strings.
+pub static REFLECT_NATIVES: [NativeFunction; 5] = [
+    NativeFunction {
+        name: "type_of",
+        arity: 1,
+        variadic: false,
+        func: type_of,
+    },
+    NativeFunction {
+        name: "arity",
+        arity: 1,
+        variadic: false,
+        func: arity,
+    },
+    NativeFunction {
+        name: "has_field",
+        arity: 2,
+        variadic: false,
+        func: has_field,
+    },
+    NativeFunction {
+        name: "get_field",
+        arity: 2,
+        variadic: false,
+        func: get_field,
+    },
+    NativeFunction {
+        name: "enum_at",
+        arity: 2,
+        variadic: false,
+        func: enum_at,
+    },
+];
+
+// Names are the ones type annotations use, so `type_of(1)` is "Number".
+// Members of an enum report the enum's name, so that `type_of(Color.Red)`
+// is "Color".
+fn type_of(_terp: &Interpreter, args: &[Literal], _paren: &Token) -> Result<Literal, LoxResult> {
+    let name = match &args[0] {
+        Literal::Nil => "Nil",
+        Literal::Func(_) => "Function",
+        Literal::Bool(_) => "Bool",
+        Literal::Number(_) => "Number",
+        Literal::String(_) => "String",
+        Literal::Enum(_) => "Enum",
+        Literal::EnumMember(member) => &member.enumeration.name,
+        Literal::Regex(_) => "Regex",
+        Literal::Set(_) => "Set",
+    };
+    Ok(Literal::String(name.to_string()))
+}
+
+fn arity(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    match &args[0] {
+        Literal::Func(function) => Ok(Literal::Number(function.arity() as f64)),
+        _ => Err(LoxResult::runtime_error(
+            paren,
+            "Argument 1 of 'arity' must be a function.",
+        )),
+    }
+}
+
+fn has_field(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let field = string_arg(paren, "has_field", args, 1)?;
+    Ok(Literal::Bool(
+        Interpreter::lookup_property(&args[0], field).is_some(),
+    ))
+}
+
+fn get_field(terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let field = string_arg(paren, "get_field", args, 1)?;
+    // report a missing field at the call site, naming the field
+    let name = Token::new(
+        TokenType::Identifier,
+        field.to_string(),
+        None,
+        paren.line,
+        paren.column,
+    );
+    terp.get_property(&args[0], &name)
+}
+
+// enum_at(E, i) is the member of E with ordinal i, for iterating with
+// `len(E)`.
+fn enum_at(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let Literal::Enum(enumeration) = &args[0] else {
+        return Err(LoxResult::runtime_error(
+            paren,
+            "Argument 1 of 'enum_at' must be an enum.",
+        ));
+    };
+    let ordinal = index_arg(paren, "enum_at", args, 1)?;
+    if ordinal >= enumeration.members.len() {
+        return Err(LoxResult::runtime_error(
+            paren,
+            "Argument 2 of 'enum_at' is out of range.",
+        ));
+    }
+    Ok(Literal::EnumMember(LoxEnumMember {
+        enumeration: Rc::clone(enumeration),
+        ordinal,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+
+    #[test]
+    fn type_of_names_primitives() {
+        assert_eq!(call(type_of, &[Literal::Nil]), Some(string("Nil")));
+        assert_eq!(
+            call(type_of, &[Literal::Number(1.0)]),
+            Some(string("Number"))
+        );
+        assert_eq!(call(type_of, &[string("s")]), Some(string("String")));
+    }
+
+    #[test]
+    fn arity_of_natives() {
+        let func = Literal::Func(Callable {
+            func: Rc::new(&REFLECT_NATIVES[3]),
+        });
+        assert_eq!(call(arity, &[func]), Some(Literal::Number(2.0)));
+        assert_eq!(call(arity, &[Literal::Nil]), None);
+    }
+
+    #[test]
+    fn field_name_must_be_a_string() {
+        assert_eq!(call(has_field, &[Literal::Nil, Literal::Number(1.0)]), None);
+    }
+
+    #[test]
+    fn enum_members_by_ordinal() {
+        let color = Literal::Enum(Rc::new(LoxEnum {
+            name: "Color".to_string(),
+            members: vec!["Red".to_string(), "Green".to_string()],
+        }));
+        assert_eq!(
+            call(enum_at, &[color.clone(), Literal::Number(1.0)])
+                .unwrap()
+                .to_string(),
+            "Color.Green"
+        );
+        assert_eq!(call(enum_at, &[color, Literal::Number(2.0)]), None);
+        assert_eq!(call(enum_at, &[Literal::Nil, Literal::Number(0.0)]), None);
+    }
+}
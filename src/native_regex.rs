@@ -0,0 +1,264 @@
+use std::rc::Rc;
+
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::native_functions::*;
+use crate::regex::*;
+use crate::token::*;
+
+// Natives taking a regex accept either a value compiled with `regex()`,
+// which is worth doing for patterns used in a loop, or a pattern string.
+pub static REGEX_NATIVES: [NativeFunction; 5] = [
+    NativeFunction {
+        name: "regex",
+        arity: 1,
+        variadic: false,
+        func: regex,
+    },
+    NativeFunction {
+        name: "regex_match",
+        arity: 2,
+        variadic: false,
+        func: regex_match,
+    },
+    NativeFunction {
+        name: "regex_find",
+        arity: 2,
+        variadic: false,
+        func: regex_find,
+    },
+    NativeFunction {
+        name: "regex_capture",
+        arity: 3,
+        variadic: false,
+        func: regex_capture,
+    },
+    NativeFunction {
+        name: "regex_replace",
+        arity: 3,
+        variadic: false,
+        func: regex_replace,
+    },
+];
+
+fn compile(paren: &Token, pattern: &str) -> Result<Rc<Regex>, LoxResult> {
+    Regex::new(pattern).map(Rc::new).map_err(|e| {
+        LoxResult::runtime_error(paren, &format!("Invalid regex '{}': {}.", pattern, e))
+    })
+}
+
+fn regex_arg(
+    paren: &Token,
+    name: &str,
+    args: &[Literal],
+    index: usize,
+) -> Result<Rc<Regex>, LoxResult> {
+    match &args[index] {
+        Literal::Regex(regex) => Ok(Rc::clone(regex)),
+        Literal::String(pattern) => compile(paren, pattern),
+        _ => Err(LoxResult::runtime_error(
+            paren,
+            &format!(
+                "Argument {} of '{}' must be a regex or a string.",
+                index + 1,
+                name
+            ),
+        )),
+    }
+}
+
+fn text(chars: &[char], (start, end): (usize, usize)) -> Literal {
+    Literal::String(chars[start..end].iter().collect())
+}
+
+fn regex(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let pattern = string_arg(paren, "regex", args, 0)?;
+    Ok(Literal::Regex(compile(paren, pattern)?))
+}
+
+// True if the pattern matches anywhere; anchor it with ^ and $ to match
+// the whole string.
+fn regex_match(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let regex = regex_arg(paren, "regex_match", args, 0)?;
+    let s = string_arg(paren, "regex_match", args, 1)?;
+    Ok(Literal::Bool(
+        regex.is_match(&s.chars().collect::<Vec<_>>()),
+    ))
+}
+
+// The first match, or nil.
+fn regex_find(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let regex = regex_arg(paren, "regex_find", args, 0)?;
+    let chars: Vec<char> = string_arg(paren, "regex_find", args, 1)?.chars().collect();
+    Ok(regex
+        .find_at(&chars, 0)
+        .and_then(|found| found.group(0))
+        .map_or(Literal::Nil, |span| text(&chars, span)))
+}
+
+// regex_capture(re, s, n) is group n of the first match, or nil when there
+// is no match or the group did not take part in it. Group 0 is the whole
+// match.
+fn regex_capture(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    let regex = regex_arg(paren, "regex_capture", args, 0)?;
+    let chars: Vec<char> = string_arg(paren, "regex_capture", args, 1)?
+        .chars()
+        .collect();
+    let group = index_arg(paren, "regex_capture", args, 2)?;
+    if group > regex.groups() {
+        return Err(LoxResult::runtime_error(
+            paren,
+            &format!("Regex '{}' has no group {}.", regex.pattern, group),
+        ));
+    }
+    Ok(regex
+        .find_at(&chars, 0)
+        .and_then(|found| found.group(group))
+        .map_or(Literal::Nil, |span| text(&chars, span)))
+}
+
+// Replaces every match. In the replacement, `$n` stands for group n (a
+// single digit) and `$$` for a literal `$`.
+fn regex_replace(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    let regex = regex_arg(paren, "regex_replace", args, 0)?;
+    let chars: Vec<char> = string_arg(paren, "regex_replace", args, 1)?
+        .chars()
+        .collect();
+    let replacement =
+        parse_replacement(paren, &regex, string_arg(paren, "regex_replace", args, 2)?)?;
+
+    let mut result = String::new();
+    let mut copied = 0;
+    let mut start = 0;
+    while let Some(found) = regex.find_at(&chars, start) {
+        result.extend(&chars[copied..found.start()]);
+        for part in &replacement {
+            match part {
+                Replacement::Text(s) => result.push_str(s),
+                Replacement::Group(group) => {
+                    if let Some((start, end)) = found.group(*group) {
+                        result.extend(&chars[start..end]);
+                    }
+                }
+            }
+        }
+        copied = found.end();
+        // step past empty matches so the search moves on
+        start = if found.end() == found.start() {
+            found.end() + 1
+        } else {
+            found.end()
+        };
+        if start > chars.len() {
+            break;
+        }
+    }
+    result.extend(&chars[copied..]);
+    Ok(Literal::String(result))
+}
+
+enum Replacement {
+    Text(String),
+    Group(usize),
+}
+
+fn parse_replacement(
+    paren: &Token,
+    regex: &Regex,
+    replacement: &str,
+) -> Result<Vec<Replacement>, LoxResult> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        let group = match (c, chars.peek()) {
+            ('$', Some('$')) => {
+                chars.next();
+                text.push('$');
+                continue;
+            }
+            ('$', Some(d)) if d.is_ascii_digit() => d.to_digit(10).unwrap() as usize,
+            _ => {
+                text.push(c);
+                continue;
+            }
+        };
+        chars.next();
+        if group > regex.groups() {
+            return Err(LoxResult::runtime_error(
+                paren,
+                &format!(
+                    "Replacement refers to group {} but regex '{}' has {}.",
+                    group,
+                    regex.pattern,
+                    regex.groups()
+                ),
+            ));
+        }
+        parts.push(Replacement::Text(std::mem::take(&mut text)));
+        parts.push(Replacement::Group(group));
+    }
+    parts.push(Replacement::Text(text));
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+
+    #[test]
+    fn accepts_compiled_regexes_and_strings() {
+        let compiled = call(regex, &[string(r"\d+")]).unwrap();
+        assert_eq!(
+            call(regex_find, &[compiled, string("id=42;")]),
+            Some(string("42"))
+        );
+        assert_eq!(
+            call(regex_match, &[string("^a"), string("ba")]),
+            Some(Literal::Bool(false))
+        );
+        assert_eq!(call(regex, &[string("(")]), None);
+        assert_eq!(call(regex_match, &[Literal::Nil, string("")]), None);
+    }
+
+    #[test]
+    fn captures_groups() {
+        let args = |n| [string(r"(\w+)=(\d+)?"), string("key=;"), Literal::Number(n)];
+        assert_eq!(call(regex_capture, &args(1.0)), Some(string("key")));
+        assert_eq!(call(regex_capture, &args(2.0)), Some(Literal::Nil));
+        assert_eq!(call(regex_capture, &args(3.0)), None);
+    }
+
+    #[test]
+    fn replaces_all_matches() {
+        assert_eq!(
+            call(
+                regex_replace,
+                &[
+                    string(r"(\w+)@(\w+)"),
+                    string("a@b, c@d"),
+                    string("$2 at $1 ($$)")
+                ]
+            ),
+            Some(string("b at a ($), d at c ($)"))
+        );
+        assert_eq!(
+            call(regex_replace, &[string("x*"), string("abc"), string("-")]),
+            Some(string("-a-b-c-"))
+        );
+        assert_eq!(
+            call(regex_replace, &[string("a"), string("abc"), string("$1")]),
+            None
+        );
+    }
+}
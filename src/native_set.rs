@@ -0,0 +1,232 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::lox_set::*;
+use crate::native_functions::*;
+use crate::token::*;
+
+// `len` also accepts sets; see native_strings.
+pub static SET_NATIVES: [NativeFunction; 8] = [
+    NativeFunction {
+        name: "set",
+        arity: 0,
+        variadic: true,
+        func: set,
+    },
+    NativeFunction {
+        name: "set_add",
+        arity: 2,
+        variadic: false,
+        func: set_add,
+    },
+    NativeFunction {
+        name: "set_remove",
+        arity: 2,
+        variadic: false,
+        func: set_remove,
+    },
+    NativeFunction {
+        name: "set_contains",
+        arity: 2,
+        variadic: false,
+        func: set_contains,
+    },
+    NativeFunction {
+        name: "set_at",
+        arity: 2,
+        variadic: false,
+        func: set_at,
+    },
+    NativeFunction {
+        name: "set_union",
+        arity: 2,
+        variadic: false,
+        func: set_union,
+    },
+    NativeFunction {
+        name: "set_intersection",
+        arity: 2,
+        variadic: false,
+        func: set_intersection,
+    },
+    NativeFunction {
+        name: "set_difference",
+        arity: 2,
+        variadic: false,
+        func: set_difference,
+    },
+];
+
+pub fn set_arg(
+    paren: &Token,
+    name: &str,
+    args: &[Literal],
+    index: usize,
+) -> Result<Rc<RefCell<LoxSet>>, LoxResult> {
+    match &args[index] {
+        Literal::Set(set) => Ok(Rc::clone(set)),
+        _ => Err(LoxResult::runtime_error(
+            paren,
+            &format!("Argument {} of '{}' must be a set.", index + 1, name),
+        )),
+    }
+}
+
+fn key_arg(
+    paren: &Token,
+    name: &str,
+    args: &[Literal],
+    index: usize,
+) -> Result<HashKey, LoxResult> {
+    HashKey::of(&args[index]).ok_or_else(|| {
+        LoxResult::runtime_error(
+            paren,
+            &format!(
+                "Argument {} of '{}' cannot be a set element.",
+                index + 1,
+                name
+            ),
+        )
+    })
+}
+
+fn new_set(set: LoxSet) -> Literal {
+    Literal::Set(Rc::new(RefCell::new(set)))
+}
+
+// set(a, b, ...) makes a set of its arguments, dropping duplicates.
+fn set(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let mut set = LoxSet::new();
+    for (index, value) in args.iter().enumerate() {
+        set.add(key_arg(paren, "set", args, index)?, value.clone());
+    }
+    Ok(new_set(set))
+}
+
+// Returns false if the value was already in the set.
+fn set_add(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let set = set_arg(paren, "set_add", args, 0)?;
+    let key = key_arg(paren, "set_add", args, 1)?;
+    let added = set.borrow_mut().add(key, args[1].clone());
+    Ok(Literal::Bool(added))
+}
+
+// Returns false if the value was not in the set.
+fn set_remove(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let set = set_arg(paren, "set_remove", args, 0)?;
+    let key = key_arg(paren, "set_remove", args, 1)?;
+    let removed = set.borrow_mut().remove(&key);
+    Ok(Literal::Bool(removed))
+}
+
+// Values that cannot be elements are simply not contained.
+fn set_contains(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    let set = set_arg(paren, "set_contains", args, 0)?;
+    let contained = HashKey::of(&args[1]).is_some_and(|key| set.borrow().contains(&key));
+    Ok(Literal::Bool(contained))
+}
+
+// set_at(s, i) is the i-th element in insertion order, for iterating with
+// `len(s)`.
+fn set_at(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let set = set_arg(paren, "set_at", args, 0)?;
+    let index = index_arg(paren, "set_at", args, 1)?;
+    let value = set.borrow().values().get(index).cloned();
+    match value {
+        Some(value) => Ok(value),
+        None => Err(LoxResult::runtime_error(
+            paren,
+            "Argument 2 of 'set_at' is out of range.",
+        )),
+    }
+}
+
+// The combining natives return a new set and leave their arguments alone.
+// Elements keep the order of the first set, then of the second.
+fn set_union(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let first = set_arg(paren, "set_union", args, 0)?;
+    let second = set_arg(paren, "set_union", args, 1)?;
+    let mut union = first.borrow().clone();
+    for value in second.borrow().values() {
+        union.add(HashKey::of(value).unwrap(), value.clone());
+    }
+    Ok(new_set(union))
+}
+
+fn set_intersection(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    let first = set_arg(paren, "set_intersection", args, 0)?;
+    let second = set_arg(paren, "set_intersection", args, 1)?;
+    let second = second.borrow();
+    let intersection = first.borrow().filter(|key| second.contains(key));
+    Ok(new_set(intersection))
+}
+
+fn set_difference(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    let first = set_arg(paren, "set_difference", args, 0)?;
+    let second = set_arg(paren, "set_difference", args, 1)?;
+    let second = second.borrow();
+    let difference = first.borrow().filter(|key| !second.contains(key));
+    Ok(new_set(difference))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+
+    fn numbers(ns: &[f64]) -> Literal {
+        let args: Vec<Literal> = ns.iter().map(|n| Literal::Number(*n)).collect();
+        call(set, &args).unwrap()
+    }
+
+    #[test]
+    fn adds_and_removes() {
+        let s = numbers(&[1.0, 2.0, 1.0]);
+        assert_eq!(s.to_string(), "{1, 2}");
+        assert_eq!(
+            call(set_add, &[s.clone(), Literal::Number(2.0)]),
+            Some(Literal::Bool(false))
+        );
+        assert_eq!(
+            call(set_remove, &[s.clone(), Literal::Number(1.0)]),
+            Some(Literal::Bool(true))
+        );
+        assert_eq!(
+            call(set_at, &[s.clone(), Literal::Number(0.0)]),
+            Some(Literal::Number(2.0))
+        );
+        assert_eq!(
+            call(set_contains, &[s.clone(), Literal::Number(f64::NAN)]),
+            Some(Literal::Bool(false))
+        );
+        assert_eq!(call(set_add, &[s, Literal::Number(f64::NAN)]), None);
+    }
+
+    #[test]
+    fn combines_sets() {
+        let a = numbers(&[1.0, 2.0, 3.0]);
+        let b = numbers(&[4.0, 3.0, 2.0]);
+        let combine = |func, a: &Literal, b: &Literal| {
+            call(func, &[a.clone(), b.clone()]).unwrap().to_string()
+        };
+        assert_eq!(combine(set_union, &a, &b), "{1, 2, 3, 4}");
+        assert_eq!(combine(set_intersection, &a, &b), "{2, 3}");
+        assert_eq!(combine(set_difference, &a, &b), "{1}");
+        assert_eq!(a.to_string(), "{1, 2, 3}");
+    }
+}
@@ -0,0 +1,315 @@
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::native_functions::*;
+use crate::token::*;
+
+// String natives count and index in characters, not bytes, so they behave
+// the same for any Unicode text.
+pub static STRING_NATIVES: [NativeFunction; 12] = [
+    NativeFunction {
+        name: "len",
+        arity: 1,
+        variadic: false,
+        func: len,
+    },
+    NativeFunction {
+        name: "substr",
+        arity: 3,
+        variadic: false,
+        func: substr,
+    },
+    NativeFunction {
+        name: "index_of",
+        arity: 2,
+        variadic: false,
+        func: index_of,
+    },
+    NativeFunction {
+        name: "trim",
+        arity: 1,
+        variadic: false,
+        func: trim,
+    },
+    NativeFunction {
+        name: "upper",
+        arity: 1,
+        variadic: false,
+        func: upper,
+    },
+    NativeFunction {
+        name: "lower",
+        arity: 1,
+        variadic: false,
+        func: lower,
+    },
+    NativeFunction {
+        name: "replace",
+        arity: 3,
+        variadic: false,
+        func: replace,
+    },
+    NativeFunction {
+        name: "starts_with",
+        arity: 2,
+        variadic: false,
+        func: starts_with,
+    },
+    NativeFunction {
+        name: "ends_with",
+        arity: 2,
+        variadic: false,
+        func: ends_with,
+    },
+    NativeFunction {
+        name: "repeat",
+        arity: 2,
+        variadic: false,
+        func: repeat,
+    },
+    NativeFunction {
+        name: "char_code",
+        arity: 1,
+        variadic: false,
+        func: char_code,
+    },
+    NativeFunction {
+        name: "from_char_code",
+        arity: 1,
+        variadic: false,
+        func: from_char_code,
+    },
+];
+
+// Also counts the elements of a set and the members of an enum.
+fn len(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let count = match &args[0] {
+        Literal::Set(set) => set.borrow().len(),
+        Literal::Enum(enumeration) => enumeration.members.len(),
+        _ => string_arg(paren, "len", args, 0)?.chars().count(),
+    };
+    Ok(Literal::Number(count as f64))
+}
+
+// substr(s, start, end) returns the characters in [start, end).
+fn substr(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "substr", args, 0)?;
+    let start = index_arg(paren, "substr", args, 1)?;
+    let end = index_arg(paren, "substr", args, 2)?;
+    let len = s.chars().count();
+
+    if start > len {
+        return Err(out_of_range(paren, "substr", 2));
+    }
+    if end < start || end > len {
+        return Err(out_of_range(paren, "substr", 3));
+    }
+    Ok(Literal::String(
+        s.chars().skip(start).take(end - start).collect(),
+    ))
+}
+
+// Returns the character position of the first match, or -1.
+fn index_of(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "index_of", args, 0)?;
+    let needle = string_arg(paren, "index_of", args, 1)?;
+    let index = match s.find(needle) {
+        Some(byte_index) => s[..byte_index].chars().count() as f64,
+        None => -1.0,
+    };
+    Ok(Literal::Number(index))
+}
+
+fn trim(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "trim", args, 0)?;
+    Ok(Literal::String(s.trim().to_string()))
+}
+
+fn upper(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "upper", args, 0)?;
+    Ok(Literal::String(s.to_uppercase()))
+}
+
+fn lower(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "lower", args, 0)?;
+    Ok(Literal::String(s.to_lowercase()))
+}
+
+// Replaces every occurrence.
+fn replace(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "replace", args, 0)?;
+    let from = string_arg(paren, "replace", args, 1)?;
+    let to = string_arg(paren, "replace", args, 2)?;
+    if from.is_empty() {
+        return Err(LoxResult::runtime_error(
+            paren,
+            "Argument 2 of 'replace' must not be empty.",
+        ));
+    }
+    Ok(Literal::String(s.replace(from, to)))
+}
+
+fn starts_with(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "starts_with", args, 0)?;
+    let prefix = string_arg(paren, "starts_with", args, 1)?;
+    Ok(Literal::Bool(s.starts_with(prefix)))
+}
+
+fn ends_with(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "ends_with", args, 0)?;
+    let suffix = string_arg(paren, "ends_with", args, 1)?;
+    Ok(Literal::Bool(s.ends_with(suffix)))
+}
+
+// Results longer than this many bytes are refused rather than exhausting
+// memory.
+const MAX_REPEAT_BYTES: usize = 1 << 28;
+
+fn repeat(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "repeat", args, 0)?;
+    let count = index_arg(paren, "repeat", args, 1)?;
+    match s.len().checked_mul(count) {
+        Some(bytes) if bytes <= MAX_REPEAT_BYTES => Ok(Literal::String(s.repeat(count))),
+        _ => Err(LoxResult::runtime_error(
+            paren,
+            "Argument 2 of 'repeat' is too large.",
+        )),
+    }
+}
+
+// Unicode code point of a one-character string.
+fn char_code(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "char_code", args, 0)?;
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Literal::Number(c as u32 as f64)),
+        _ => Err(LoxResult::runtime_error(
+            paren,
+            "Argument 1 of 'char_code' must be a single character.",
+        )),
+    }
+}
+
+fn from_char_code(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    let code = index_arg(paren, "from_char_code", args, 0)?;
+    match u32::try_from(code).ok().and_then(char::from_u32) {
+        Some(c) => Ok(Literal::String(c.to_string())),
+        None => Err(LoxResult::runtime_error(
+            paren,
+            "Argument 1 of 'from_char_code' is not a valid code point.",
+        )),
+    }
+}
+
+fn out_of_range(paren: &Token, name: &str, position: usize) -> LoxResult {
+    LoxResult::runtime_error(
+        paren,
+        &format!("Argument {} of '{}' is out of range.", position, name),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+
+    #[test]
+    fn counts_characters_not_bytes() {
+        assert_eq!(call(len, &[string("héllo ☕")]), Some(Literal::Number(7.0)));
+        assert_eq!(
+            call(
+                substr,
+                &[
+                    string("héllo ☕"),
+                    Literal::Number(1.0),
+                    Literal::Number(4.0)
+                ]
+            ),
+            Some(string("éll"))
+        );
+        assert_eq!(
+            call(index_of, &[string("日本語"), string("語")]),
+            Some(Literal::Number(2.0))
+        );
+    }
+
+    #[test]
+    fn substr_rejects_bad_ranges() {
+        let s = string("abc");
+        assert_eq!(
+            call(
+                substr,
+                &[s.clone(), Literal::Number(2.0), Literal::Number(1.0)]
+            ),
+            None
+        );
+        assert_eq!(
+            call(
+                substr,
+                &[s.clone(), Literal::Number(0.0), Literal::Number(4.0)]
+            ),
+            None
+        );
+        assert_eq!(
+            call(substr, &[s, Literal::Number(0.5), Literal::Number(1.0)]),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_argument_types() {
+        assert_eq!(call(upper, &[Literal::Number(1.0)]), None);
+        assert_eq!(call(repeat, &[string("a"), string("3")]), None);
+    }
+
+    #[test]
+    fn repeat_limits_the_result_size() {
+        assert_eq!(call(repeat, &[string("ab"), Literal::Number(1e19)]), None);
+        assert_eq!(call(repeat, &[string("ab"), Literal::Number(1e11)]), None);
+        assert_eq!(
+            call(repeat, &[string(""), Literal::Number(1e19)]),
+            Some(string(""))
+        );
+    }
+
+    #[test]
+    fn char_codes_round_trip() {
+        assert_eq!(
+            call(char_code, &[string("☕")]),
+            Some(Literal::Number(9749.0))
+        );
+        assert_eq!(
+            call(from_char_code, &[Literal::Number(9749.0)]),
+            Some(string("☕"))
+        );
+        assert_eq!(call(char_code, &[string("ab")]), None);
+        assert_eq!(call(from_char_code, &[Literal::Number(55296.0)]), None);
+    }
+
+    #[test]
+    fn transforms() {
+        assert_eq!(call(trim, &[string("  hi \n")]), Some(string("hi")));
+        assert_eq!(call(upper, &[string("straße")]), Some(string("STRASSE")));
+        assert_eq!(call(lower, &[string("ÀB")]), Some(string("àb")));
+        assert_eq!(
+            call(replace, &[string("a-b-c"), string("-"), string("+")]),
+            Some(string("a+b+c"))
+        );
+        assert_eq!(
+            call(repeat, &[string("ab"), Literal::Number(3.0)]),
+            Some(string("ababab"))
+        );
+        assert_eq!(
+            call(starts_with, &[string("prefix"), string("pre")]),
+            Some(Literal::Bool(true))
+        );
+        assert_eq!(
+            call(ends_with, &[string("prefix"), string("pre")]),
+            Some(Literal::Bool(false))
+        );
+    }
+}
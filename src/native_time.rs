@@ -0,0 +1,621 @@
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime};
+
+use crate::error::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::native_functions::*;
+use crate::token::*;
+
+// Times are numbers of milliseconds since 1970-01-01T00:00:00Z, the same
+// scale as `clock()`, and durations are plain milliseconds too, so time
+// arithmetic is ordinary `+` and `-`. Everything is in UTC; no time zone
+// database is consulted.
+pub static TIME_NATIVES: [NativeFunction; 19] = [
+    NativeFunction {
+        name: "clock",
+        arity: 0,
+        variadic: false,
+        func: clock,
+    },
+    NativeFunction {
+        name: "now_ns",
+        arity: 0,
+        variadic: false,
+        func: now_ns,
+    },
+    NativeFunction {
+        name: "date",
+        arity: 3,
+        variadic: true,
+        func: date,
+    },
+    NativeFunction {
+        name: "format_iso",
+        arity: 1,
+        variadic: false,
+        func: format_iso,
+    },
+    NativeFunction {
+        name: "parse_iso",
+        arity: 1,
+        variadic: false,
+        func: parse_iso,
+    },
+    NativeFunction {
+        name: "date_year",
+        arity: 1,
+        variadic: false,
+        func: date_year,
+    },
+    NativeFunction {
+        name: "date_month",
+        arity: 1,
+        variadic: false,
+        func: date_month,
+    },
+    NativeFunction {
+        name: "date_day",
+        arity: 1,
+        variadic: false,
+        func: date_day,
+    },
+    NativeFunction {
+        name: "date_hour",
+        arity: 1,
+        variadic: false,
+        func: date_hour,
+    },
+    NativeFunction {
+        name: "date_minute",
+        arity: 1,
+        variadic: false,
+        func: date_minute,
+    },
+    NativeFunction {
+        name: "date_second",
+        arity: 1,
+        variadic: false,
+        func: date_second,
+    },
+    NativeFunction {
+        name: "date_millisecond",
+        arity: 1,
+        variadic: false,
+        func: date_millisecond,
+    },
+    NativeFunction {
+        name: "date_weekday",
+        arity: 1,
+        variadic: false,
+        func: date_weekday,
+    },
+    NativeFunction {
+        name: "date_day_of_year",
+        arity: 1,
+        variadic: false,
+        func: date_day_of_year,
+    },
+    NativeFunction {
+        name: "duration_days",
+        arity: 1,
+        variadic: false,
+        func: duration_days,
+    },
+    NativeFunction {
+        name: "duration_hours",
+        arity: 1,
+        variadic: false,
+        func: duration_hours,
+    },
+    NativeFunction {
+        name: "duration_minutes",
+        arity: 1,
+        variadic: false,
+        func: duration_minutes,
+    },
+    NativeFunction {
+        name: "duration_seconds",
+        arity: 1,
+        variadic: false,
+        func: duration_seconds,
+    },
+    NativeFunction {
+        name: "duration_milliseconds",
+        arity: 1,
+        variadic: false,
+        func: duration_milliseconds,
+    },
+];
+
+const MS_PER_SECOND: i64 = 1000;
+const MS_PER_MINUTE: i64 = 60 * MS_PER_SECOND;
+const MS_PER_HOUR: i64 = 60 * MS_PER_MINUTE;
+const MS_PER_DAY: i64 = 24 * MS_PER_HOUR;
+
+// A time broken down into its UTC calendar fields.
+#[derive(Debug, PartialEq)]
+struct DateTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    millisecond: i64,
+}
+
+const MAX_YEAR: i64 = 200_000;
+
+impl DateTime {
+    fn from_ms(ms: i64) -> DateTime {
+        let days = ms.div_euclid(MS_PER_DAY);
+        let rest = ms.rem_euclid(MS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        DateTime {
+            year,
+            month,
+            day,
+            hour: rest / MS_PER_HOUR,
+            minute: rest % MS_PER_HOUR / MS_PER_MINUTE,
+            second: rest % MS_PER_MINUTE / MS_PER_SECOND,
+            millisecond: rest % MS_PER_SECOND,
+        }
+    }
+
+    // `None` when a field is out of range, e.g. February 30th. Years are
+    // limited so that the result stays an exactly representable number.
+    fn to_ms(&self) -> Option<i64> {
+        let in_range = (-MAX_YEAR..=MAX_YEAR).contains(&self.year)
+            && (1..=12).contains(&self.month)
+            && (1..=days_in_month(self.year, self.month)).contains(&self.day)
+            && (0..24).contains(&self.hour)
+            && (0..60).contains(&self.minute)
+            && (0..60).contains(&self.second)
+            && (0..1000).contains(&self.millisecond);
+        in_range.then(|| {
+            days_from_civil(self.year, self.month, self.day) * MS_PER_DAY
+                + self.hour * MS_PER_HOUR
+                + self.minute * MS_PER_MINUTE
+                + self.second * MS_PER_SECOND
+                + self.millisecond
+        })
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar, after Howard
+// Hinnant's `days_from_civil`.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Years outside 0..=9999 use ISO 8601's expanded form, a sign and six
+// digits, as JavaScript's toISOString does.
+fn format(time: &DateTime) -> String {
+    let year = if (0..=9999).contains(&time.year) {
+        format!("{:04}", time.year)
+    } else {
+        format!("{:+07}", time.year)
+    };
+    format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, time.month, time.day, time.hour, time.minute, time.second, time.millisecond
+    )
+}
+
+// Accepts "YYYY-MM-DD", optionally followed by "THH:MM", ":SS" and ".fff"
+// (up to millisecond precision is kept), and then either "Z" or a "+HH:MM"
+// / "-HH:MM" offset. A date on its own is midnight UTC.
+fn parse(s: &str) -> Option<i64> {
+    let mut parser = IsoParser {
+        chars: s.chars().peekable(),
+    };
+
+    let year = match parser.chars.peek() {
+        Some('+') => parser.after('+', 6)?,
+        Some('-') => -parser.after('-', 6)?,
+        _ => parser.number(4)?,
+    };
+    let mut time = DateTime {
+        year,
+        month: parser.after('-', 2)?,
+        day: parser.after('-', 2)?,
+        hour: 0,
+        minute: 0,
+        second: 0,
+        millisecond: 0,
+    };
+    if parser.chars.peek().is_none() {
+        return time.to_ms();
+    }
+
+    parser.expect('T')?;
+    time.hour = parser.number(2)?;
+    time.minute = parser.after(':', 2)?;
+    if parser.chars.peek() == Some(&':') {
+        time.second = parser.after(':', 2)?;
+        if parser.chars.peek() == Some(&'.') {
+            time.millisecond = parser.fraction()?;
+        }
+    }
+
+    let offset = match parser.chars.next()? {
+        'Z' => 0,
+        sign @ ('+' | '-') => {
+            let hours = parser.number(2)?;
+            let minutes = parser.after(':', 2)?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = hours * MS_PER_HOUR + minutes * MS_PER_MINUTE;
+            if sign == '+' {
+                offset
+            } else {
+                -offset
+            }
+        }
+        _ => return None,
+    };
+    if parser.chars.next().is_some() {
+        return None;
+    }
+    Some(time.to_ms()? - offset)
+}
+
+struct IsoParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl IsoParser<'_> {
+    fn expect(&mut self, c: char) -> Option<()> {
+        (self.chars.next()? == c).then_some(())
+    }
+
+    // exactly `width` digits
+    fn number(&mut self, width: usize) -> Option<i64> {
+        let mut n = 0;
+        for _ in 0..width {
+            n = n * 10 + self.chars.next()?.to_digit(10)? as i64;
+        }
+        Some(n)
+    }
+
+    fn after(&mut self, separator: char, width: usize) -> Option<i64> {
+        self.expect(separator)?;
+        self.number(width)
+    }
+
+    // ".f", ".ff", ".fff" or longer, truncated to milliseconds
+    fn fraction(&mut self) -> Option<i64> {
+        self.expect('.')?;
+        let mut ms = 0;
+        let mut count = 0;
+        while let Some(digit) = self.chars.peek().and_then(|c| c.to_digit(10)) {
+            self.chars.next();
+            if count < 3 {
+                ms = ms * 10 + digit as i64;
+            }
+            count += 1;
+        }
+        match count {
+            0 => None,
+            1 => Some(ms * 100),
+            2 => Some(ms * 10),
+            _ => Some(ms),
+        }
+    }
+}
+
+// Times are limited to the years `date` and `parse_iso` accept, so that
+// whatever `format_iso` prints parses back.
+fn time_arg(paren: &Token, name: &str, args: &[Literal]) -> Result<DateTime, LoxResult> {
+    let time = DateTime::from_ms(integer_arg(paren, name, args, 0)?);
+    if (-MAX_YEAR..=MAX_YEAR).contains(&time.year) {
+        Ok(time)
+    } else {
+        Err(LoxResult::runtime_error(
+            paren,
+            &format!("Argument 1 of '{}' is out of range.", name),
+        ))
+    }
+}
+
+// Milliseconds since the epoch, from the system clock.
+fn clock(_terp: &Interpreter, _args: &[Literal], _paren: &Token) -> Result<Literal, LoxResult> {
+    match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(n) => Ok(Literal::Number(n.as_millis() as f64)),
+        Err(e) => Err(LoxResult::system_error(&format!(
+            "Clock returned invalid duration: {:?}",
+            e.duration()
+        ))),
+    }
+}
+
+// Nanoseconds from a monotonic clock. Only differences between readings
+// are meaningful, which makes it suitable for timing code.
+fn now_ns(_terp: &Interpreter, _args: &[Literal], _paren: &Token) -> Result<Literal, LoxResult> {
+    static START: OnceLock<Instant> = OnceLock::new();
+    let start = START.get_or_init(Instant::now);
+    Ok(Literal::Number(start.elapsed().as_nanos() as f64))
+}
+
+// date(year, month, day, [hour, [minute, [second, [millisecond]]]])
+fn date(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    if args.len() > 7 {
+        return Err(LoxResult::runtime_error(
+            paren,
+            &format!("Expected at most 7 arguments but got {}.", args.len()),
+        ));
+    }
+    let mut fields = [0; 7];
+    for (index, field) in fields.iter_mut().enumerate().take(args.len()) {
+        *field = integer_arg(paren, "date", args, index)?;
+    }
+    let time = DateTime {
+        year: fields[0],
+        month: fields[1],
+        day: fields[2],
+        hour: fields[3],
+        minute: fields[4],
+        second: fields[5],
+        millisecond: fields[6],
+    };
+    match time.to_ms() {
+        Some(ms) => Ok(Literal::Number(ms as f64)),
+        None => Err(LoxResult::runtime_error(
+            paren,
+            "Arguments of 'date' do not name a valid date and time.",
+        )),
+    }
+}
+
+fn format_iso(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let time = time_arg(paren, "format_iso", args)?;
+    Ok(Literal::String(format(&time)))
+}
+
+// Malformed timestamps give nil, like `num` does for malformed numbers.
+fn parse_iso(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    let s = string_arg(paren, "parse_iso", args, 0)?;
+    Ok(parse(s).map_or(Literal::Nil, |ms| Literal::Number(ms as f64)))
+}
+
+fn field(
+    paren: &Token,
+    name: &str,
+    args: &[Literal],
+    get: fn(&DateTime) -> i64,
+) -> Result<Literal, LoxResult> {
+    Ok(Literal::Number(get(&time_arg(paren, name, args)?) as f64))
+}
+
+fn date_year(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    field(paren, "date_year", args, |t| t.year)
+}
+
+// 1 for January through 12 for December.
+fn date_month(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    field(paren, "date_month", args, |t| t.month)
+}
+
+fn date_day(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    field(paren, "date_day", args, |t| t.day)
+}
+
+fn date_hour(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    field(paren, "date_hour", args, |t| t.hour)
+}
+
+fn date_minute(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    field(paren, "date_minute", args, |t| t.minute)
+}
+
+fn date_second(_terp: &Interpreter, args: &[Literal], paren: &Token) -> Result<Literal, LoxResult> {
+    field(paren, "date_second", args, |t| t.second)
+}
+
+fn date_millisecond(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    field(paren, "date_millisecond", args, |t| t.millisecond)
+}
+
+// ISO weekday: 1 for Monday through 7 for Sunday.
+fn date_weekday(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    let ms = integer_arg(paren, "date_weekday", args, 0)?;
+    // 1970-01-01 was a Thursday
+    let weekday = (ms.div_euclid(MS_PER_DAY) + 3).rem_euclid(7) + 1;
+    Ok(Literal::Number(weekday as f64))
+}
+
+// 1 for January 1st.
+fn date_day_of_year(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    let ms = integer_arg(paren, "date_day_of_year", args, 0)?;
+    let days = ms.div_euclid(MS_PER_DAY);
+    let (year, _, _) = civil_from_days(days);
+    Ok(Literal::Number(
+        (days - days_from_civil(year, 1, 1) + 1) as f64,
+    ))
+}
+
+fn duration(paren: &Token, name: &str, args: &[Literal], unit: i64) -> Result<Literal, LoxResult> {
+    Ok(Literal::Number(
+        number_arg(paren, name, args, 0)? * unit as f64,
+    ))
+}
+
+fn duration_days(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    duration(paren, "duration_days", args, MS_PER_DAY)
+}
+
+fn duration_hours(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    duration(paren, "duration_hours", args, MS_PER_HOUR)
+}
+
+fn duration_minutes(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    duration(paren, "duration_minutes", args, MS_PER_MINUTE)
+}
+
+fn duration_seconds(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    duration(paren, "duration_seconds", args, MS_PER_SECOND)
+}
+
+// The identity on durations, for symmetry with the other units.
+fn duration_milliseconds(
+    _terp: &Interpreter,
+    args: &[Literal],
+    paren: &Token,
+) -> Result<Literal, LoxResult> {
+    duration(paren, "duration_milliseconds", args, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::test_support::*;
+
+    #[test]
+    fn civil_days_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        for days in (-800_000..800_000).step_by(997) {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn formats_and_parses_iso_timestamps() {
+        let ms = 951_782_400_123; // 2000-02-29T00:00:00.123Z
+        assert_eq!(format(&DateTime::from_ms(ms)), "2000-02-29T00:00:00.123Z");
+        assert_eq!(parse("2000-02-29T00:00:00.123Z"), Some(ms));
+        assert_eq!(parse("2000-02-29T00:00:00.1234Z"), Some(ms));
+        assert_eq!(parse("2000-02-29"), Some(ms - 123));
+        assert_eq!(parse("2000-02-29T01:30+01:30"), Some(ms - 123));
+        assert_eq!(parse("1969-12-31T23:59:59.999Z"), Some(-1));
+        assert_eq!(format(&DateTime::from_ms(-1)), "1969-12-31T23:59:59.999Z");
+    }
+
+    #[test]
+    fn round_trips_at_the_edges_of_the_year_range() {
+        let time = |year, month, day, hour, minute, second, millisecond| DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            millisecond,
+        };
+        for (time, text) in [
+            (time(0, 1, 1, 0, 0, 0, 0), "0000-01-01T00:00:00.000Z"),
+            (time(-1, 1, 1, 0, 0, 0, 0), "-000001-01-01T00:00:00.000Z"),
+            (
+                time(9999, 12, 31, 23, 59, 59, 999),
+                "9999-12-31T23:59:59.999Z",
+            ),
+            (time(10000, 1, 1, 0, 0, 0, 0), "+010000-01-01T00:00:00.000Z"),
+            (time(12000, 6, 1, 0, 0, 0, 0), "+012000-06-01T00:00:00.000Z"),
+            (
+                time(-MAX_YEAR, 1, 1, 0, 0, 0, 0),
+                "-200000-01-01T00:00:00.000Z",
+            ),
+            (
+                time(MAX_YEAR, 12, 31, 23, 59, 59, 999),
+                "+200000-12-31T23:59:59.999Z",
+            ),
+        ] {
+            let ms = time.to_ms().unwrap();
+            assert_eq!(format(&DateTime::from_ms(ms)), text);
+            assert_eq!(parse(text), Some(ms));
+        }
+        assert_eq!(parse("+200001-01-01"), None);
+        assert_eq!(parse("12000-01-01"), None);
+        assert_eq!(parse("+12000-01-01"), None);
+
+        let past_the_range = [Literal::Number(1e16)];
+        assert_eq!(call(format_iso, &past_the_range), None);
+    }
+
+    #[test]
+    fn rejects_malformed_timestamps() {
+        assert_eq!(parse("2001-02-29"), None);
+        assert_eq!(parse("2000-13-01"), None);
+        assert_eq!(parse("2000-00-01"), None);
+        assert_eq!(parse("2000-1-01"), None);
+        assert_eq!(parse("2000-01-01T10:00"), None);
+        assert_eq!(parse("2000-01-01T24:00Z"), None);
+        assert_eq!(parse("2000-01-01T10:00Zjunk"), None);
+        assert_eq!(parse("2000-01-01T10:00:00.Z"), None);
+        assert_eq!(parse("2024-01-01T00:00:00+99:99"), None);
+        assert_eq!(parse("2024-01-01T00:00:00+24:00"), None);
+        assert_eq!(parse("2024-01-01T00:00:00-00:60"), None);
+    }
+
+    #[test]
+    fn weekday_and_day_of_year() {
+        // 2024-12-31 is a Tuesday and the 366th day of a leap year
+        let ms = [Literal::Number(parse("2024-12-31T23:00Z").unwrap() as f64)];
+        assert_eq!(call(date_weekday, &ms), Some(Literal::Number(2.0)));
+        assert_eq!(call(date_day_of_year, &ms), Some(Literal::Number(366.0)));
+    }
+}
@@ -8,16 +8,22 @@ use crate::token_type::*;
 
 pub struct Parser<'a> {
     tokens: &'a [Token],
+    // the text `tokens` were scanned from
+    source: &'a str,
     current: usize,
     had_error: bool,
+    // labels of the loops enclosing the statement being parsed
+    labels: Vec<String>,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(tokens: &[Token]) -> Parser {
+    pub fn new(tokens: &'a [Token], source: &'a str) -> Parser<'a> {
         Parser {
             tokens,
+            source,
             current: 0,
             had_error: false,
+            labels: Vec::new(),
         }
     }
 
@@ -40,6 +46,8 @@ impl<'a> Parser<'a> {
     fn declaration(&mut self) -> Result<Stmt, LoxResult> {
         let result = if self.is_match(&[TokenType::Var]) {
             self.var_declaration()
+        } else if self.is_match(&[TokenType::Enum]) {
+            self.enum_declaration()
         } else {
             self.statement()
         };
@@ -52,13 +60,20 @@ impl<'a> Parser<'a> {
     }
 
     fn statement(&mut self) -> Result<Stmt, LoxResult> {
-        if self.is_match(&[TokenType::Break]) {
-            let token = self.peek().dup();
-            self.consume(TokenType::SemiColon, "Expect ';' after break statement.")?;
-            return Ok(Stmt::Break(BreakStmt { token }));
+        if self.is_match(&[TokenType::Assert]) {
+            return self.assert_statement();
+        }
+        if self.is_match(&[TokenType::Break, TokenType::Continue]) {
+            return self.jump_statement();
+        }
+        if self.check(TokenType::Identifier) && self.check_next(TokenType::Colon) {
+            return self.labeled_statement();
+        }
+        if self.is_match(&[TokenType::Do]) {
+            return self.do_while_statement(None);
         }
         if self.is_match(&[TokenType::For]) {
-            return self.for_statement();
+            return self.for_statement(None);
         }
         if self.is_match(&[TokenType::If]) {
             return self.if_statement();
@@ -67,18 +82,115 @@ impl<'a> Parser<'a> {
             return self.print_statement();
         }
         if self.is_match(&[TokenType::While]) {
-            return self.while_statement();
+            return self.while_statement(None);
         }
         if self.is_match(&[TokenType::LeftBrace]) {
-            return Ok(Stmt::Block(BlockStmt {
+            Ok(Stmt::Block(BlockStmt {
                 statements: self.block()?,
-            }));
+            }))
+        } else {
+            self.expression_statement()
+        }
+    }
+    // assertStmt     → "assert" expression ( "," expression )? ";" ;
+    fn assert_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let keyword = self.previous().dup();
+        let start = self.current;
+        let condition = self.expression()?;
+        let source = self.source_text(start, self.current);
+
+        let message = if self.is_match(&[TokenType::Comma]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::SemiColon, "Expect ';' after assert statement.")?;
+
+        Ok(Stmt::Assert(AssertStmt {
+            keyword,
+            condition,
+            message,
+            source,
+        }))
+    }
+
+    // jumpStmt       → ( "break" | "continue" ) IDENTIFIER? ";" ;
+    fn jump_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let token = self.previous().dup();
+
+        let label = if self.check(TokenType::Identifier) {
+            let label = self.advance().dup();
+            if !self.labels.contains(label.as_string()) {
+                return Err(self.error(&label, "Undefined loop label."));
+            }
+            Some(label)
+        } else {
+            None
+        };
+
+        self.consume(
+            TokenType::SemiColon,
+            &format!("Expect ';' after {} statement.", token.as_string()),
+        )?;
+
+        if token.is(TokenType::Break) {
+            Ok(Stmt::Break(BreakStmt { token, label }))
+        } else {
+            Ok(Stmt::Continue(ContinueStmt { token, label }))
+        }
+    }
+
+    // labeledStmt    → IDENTIFIER ":" ( whileStmt | forStmt | doWhileStmt ) ;
+    fn labeled_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let label = self.advance().dup();
+        self.consume(TokenType::Colon, "Expect ':' after label.")?;
+
+        if self.labels.contains(label.as_string()) {
+            return Err(self.error(&label, "Label is already used by an enclosing loop."));
+        }
+
+        if self.is_match(&[TokenType::While]) {
+            self.while_statement(Some(label))
+        } else if self.is_match(&[TokenType::For]) {
+            self.for_statement(Some(label))
+        } else if self.is_match(&[TokenType::Do]) {
+            self.do_while_statement(Some(label))
         } else {
-            return self.expression_statement();
+            let peek = self.peek().dup();
+            Err(self.error(&peek, "Expect loop after label."))
         }
     }
+
+    // Parses a loop body with `label` (if any) in scope for break/continue.
+    fn loop_body(&mut self, label: &Option<Token>) -> Result<Stmt, LoxResult> {
+        if let Some(label) = label {
+            self.labels.push(label.as_string().to_string());
+        }
+        let body = self.statement();
+        if label.is_some() {
+            self.labels.pop();
+        }
+        body
+    }
+
+    // doWhileStmt    → "do" statement "while" "(" expression ")" ";" ;
+    fn do_while_statement(&mut self, label: Option<Token>) -> Result<Stmt, LoxResult> {
+        let body = Box::new(self.loop_body(&label)?);
+        self.consume(TokenType::While, "Expect 'while' after do-loop body.")?;
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+        self.consume(TokenType::SemiColon, "Expect ';' after do-while loop.")?;
+
+        Ok(Stmt::Do(DoStmt {
+            body,
+            condition,
+            label,
+        }))
+    }
+
     // forStmt        → "for" "(" ( varDecl | exprStmt | ";" ) expression? ";" expression? ")" statement ;
-    fn for_statement(&mut self) -> Result<Stmt, LoxResult> {
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Stmt, LoxResult> {
         self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
 
         let initializer = if self.is_match(&[TokenType::SemiColon]) {
@@ -105,19 +217,13 @@ impl<'a> Parser<'a> {
 
         self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
 
-        let mut body = self.statement()?;
+        let body = self.loop_body(&label)?;
 
         // begin to desugar
 
-        // increment clause
-        if let Some(incr) = increment {
-            body = Stmt::Block(BlockStmt {
-                statements: vec![body, Stmt::Expression(ExpressionStmt { expression: incr })],
-            });
-        }
-
-        // replacing body with a block that contains the original body followed by an expression statement that evaluates the increment.
-        body = Stmt::While(WhileStmt {
+        // the increment stays on the while node instead of being appended
+        // to the body, so that `continue` still runs it.
+        let mut body = Stmt::While(WhileStmt {
             condition: if let Some(cond) = condition {
                 cond
             } else {
@@ -126,6 +232,8 @@ impl<'a> Parser<'a> {
                 })
             },
             body: Box::new(body),
+            increment,
+            label,
         });
 
         // have initializer
@@ -166,6 +274,12 @@ impl<'a> Parser<'a> {
     fn var_declaration(&mut self) -> Result<Stmt, LoxResult> {
         let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
 
+        let type_annotation = if self.is_match(&[TokenType::Colon]) {
+            Some(self.consume(TokenType::Identifier, "Expect type name after ':'.")?)
+        } else {
+            None
+        };
+
         let initializer = if self.is_match(&[TokenType::Equal]) {
             Some(self.expression()?)
         } else {
@@ -177,16 +291,46 @@ impl<'a> Parser<'a> {
             "Expect ';' after variable declaration.",
         )?;
 
-        Ok(Stmt::Var(VarStmt { name, initializer }))
+        Ok(Stmt::Var(VarStmt {
+            name,
+            type_annotation,
+            initializer,
+        }))
     }
 
-    fn while_statement(&mut self) -> Result<Stmt, LoxResult> {
+    fn enum_declaration(&mut self) -> Result<Stmt, LoxResult> {
+        let name = self.consume(TokenType::Identifier, "Expect enum name.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before enum body.")?;
+
+        let mut members: Vec<Token> = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            let member = self.consume(TokenType::Identifier, "Expect enum member name.")?;
+            if members.iter().any(|m| m.as_string() == member.as_string()) {
+                self.error(&member, "Duplicate member in enum.");
+            }
+            members.push(member);
+            if !self.is_match(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after enum body.")?;
+
+        Ok(Stmt::Enum(EnumStmt { name, members }))
+    }
+
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt, LoxResult> {
         self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
         let condition = self.expression()?;
         self.consume(TokenType::RightParen, "Expect ')' after 'while'.")?;
-        let body = Box::new(self.statement()?);
+        let body = Box::new(self.loop_body(&label)?);
 
-        Ok(Stmt::While(WhileStmt { condition, body }))
+        Ok(Stmt::While(WhileStmt {
+            condition,
+            body,
+            increment: None,
+            label,
+        }))
     }
 
     pub fn expression_statement(&mut self) -> Result<Stmt, LoxResult> {
@@ -275,7 +419,7 @@ impl<'a> Parser<'a> {
     }
 
     fn comparison(&mut self) -> Result<Expr, LoxResult> {
-        let mut expr: Expr = self.term()?;
+        let mut expr: Expr = self.bit_or()?;
 
         while self.is_match(&[
             TokenType::Greater,
@@ -283,6 +427,74 @@ impl<'a> Parser<'a> {
             TokenType::Less,
             TokenType::LessEqual,
         ]) {
+            let operator: Token = self.previous().dup();
+            let right: Expr = self.bit_or()?;
+            expr = Expr::Binary(BinaryExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        Ok(expr)
+    }
+
+    // The bitwise operators bind tighter than comparisons, as in Python, so
+    // `flags & MASK == 0` tests the masked bits and `1 << n < limit` compares
+    // the shifted value. Among themselves they follow C: `&` before `^`
+    // before `|`, and shifts tighter than all three.
+    // bitOr          → bitXor ( "|" bitXor )* ;
+    fn bit_or(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr: Expr = self.bit_xor()?;
+
+        while self.is_match(&[TokenType::Pipe]) {
+            let operator: Token = self.previous().dup();
+            let right: Expr = self.bit_xor()?;
+            expr = Expr::Binary(BinaryExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        Ok(expr)
+    }
+
+    // bitXor         → bitAnd ( "^" bitAnd )* ;
+    fn bit_xor(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr: Expr = self.bit_and()?;
+
+        while self.is_match(&[TokenType::Caret]) {
+            let operator: Token = self.previous().dup();
+            let right: Expr = self.bit_and()?;
+            expr = Expr::Binary(BinaryExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        Ok(expr)
+    }
+
+    // bitAnd         → shift ( "&" shift )* ;
+    fn bit_and(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr: Expr = self.shift()?;
+
+        while self.is_match(&[TokenType::Ampersand]) {
+            let operator: Token = self.previous().dup();
+            let right: Expr = self.shift()?;
+            expr = Expr::Binary(BinaryExpr {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        Ok(expr)
+    }
+
+    // shift          → term ( ( "<<" | ">>" ) term )* ;
+    fn shift(&mut self) -> Result<Expr, LoxResult> {
+        let mut expr: Expr = self.term()?;
+
+        while self.is_match(&[TokenType::LessLess, TokenType::GreaterGreater]) {
             let operator: Token = self.previous().dup();
             let right: Expr = self.term()?;
             expr = Expr::Binary(BinaryExpr {
@@ -325,7 +537,7 @@ impl<'a> Parser<'a> {
     }
 
     fn unary(&mut self) -> Result<Expr, LoxResult> {
-        if self.is_match(&[TokenType::Bang, TokenType::Minus]) {
+        if self.is_match(&[TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
             let operator: Token = self.previous().dup();
             let right: Expr = self.unary()?;
             return Ok(Expr::Unary(UnaryExpr {
@@ -336,7 +548,7 @@ impl<'a> Parser<'a> {
         self.call()
     }
 
-    fn finish_call(&mut self, callee: &Rc<Expr>) -> Result<Expr, LoxResult> {
+    fn finish_call(&mut self, callee: &Rc<Expr>, optional: bool) -> Result<Expr, LoxResult> {
         let mut arguments = Vec::new();
 
         if !self.check(TokenType::RightParen) {
@@ -360,6 +572,7 @@ impl<'a> Parser<'a> {
             callee: Rc::clone(callee),
             paren,
             arguments,
+            optional,
         }))
     }
 
@@ -368,7 +581,28 @@ impl<'a> Parser<'a> {
 
         loop {
             if self.is_match(&[TokenType::LeftParen]) {
-                expr = self.finish_call(&Rc::new(expr))?;
+                expr = self.finish_call(&Rc::new(expr), false)?;
+            } else if self.is_match(&[TokenType::Dot]) {
+                let name =
+                    self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
+                expr = Expr::Get(GetExpr {
+                    object: Box::new(expr),
+                    name,
+                    optional: false,
+                });
+            } else if self.is_match(&[TokenType::QuestionDot]) {
+                // `a?.(args)` is an optional call, `a?.name` an optional property access
+                if self.is_match(&[TokenType::LeftParen]) {
+                    expr = self.finish_call(&Rc::new(expr), true)?;
+                } else {
+                    let name =
+                        self.consume(TokenType::Identifier, "Expect property name after '?.'.")?;
+                    expr = Expr::Get(GetExpr {
+                        object: Box::new(expr),
+                        name,
+                        optional: true,
+                    });
+                }
             } else {
                 break;
             }
@@ -415,6 +649,15 @@ impl<'a> Parser<'a> {
         Err(LoxResult::parse_error(&peek, "Expect expression."))
     }
 
+    // The source text spanned by tokens[start..end], for messages that
+    // quote the user's code.
+    fn source_text(&self, start: usize, end: usize) -> String {
+        let first = &self.tokens[start];
+        let last = &self.tokens[end - 1];
+        let len = last.offset + last.as_string().chars().count() - first.offset;
+        self.source.chars().skip(first.offset).take(len).collect()
+    }
+
     fn is_match(&mut self, ttypes: &[TokenType]) -> bool {
         for &t in ttypes {
             if self.check(t) {
@@ -447,6 +690,8 @@ impl<'a> Parser<'a> {
             if matches!(
                 self.peek().token_type(),
                 TokenType::Class
+                    | TokenType::Do
+                    | TokenType::Enum
                     | TokenType::Fun
                     | TokenType::Var
                     | TokenType::For
@@ -469,6 +714,13 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn check_next(&self, ttype: TokenType) -> bool {
+        match self.tokens.get(self.current + 1) {
+            Some(token) => token.is(ttype),
+            None => false,
+        }
+    }
+
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -488,3 +740,75 @@ impl<'a> Parser<'a> {
         self.tokens.get(self.current - 1).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::*;
+
+    // Parses a single expression statement and prints its tree with every
+    // binary operation parenthesized.
+    fn tree(source: &str) -> String {
+        let source = format!("{source};");
+        let mut scanner = Scanner::new(source.clone());
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut statements = Parser::new(tokens, &source).parse().unwrap();
+        match statements.pop() {
+            Some(Stmt::Expression(stmt)) => print(&stmt.expression),
+            _ => panic!("not an expression statement: {source}"),
+        }
+    }
+
+    fn print(expr: &Expr) -> String {
+        match expr {
+            Expr::Binary(binary) => format!(
+                "({} {} {})",
+                print(&binary.left),
+                binary.operator.as_string(),
+                print(&binary.right)
+            ),
+            Expr::Unary(unary) => {
+                format!("({}{})", unary.operator.as_string(), print(&unary.right))
+            }
+            Expr::Grouping(group) => print(&group.expression),
+            Expr::Literal(literal) => literal.value.as_ref().unwrap().to_string(),
+            Expr::Variable(variable) => variable.name.as_string().to_string(),
+            _ => panic!("unexpected expression in test"),
+        }
+    }
+
+    #[test]
+    fn shifts_bind_tighter_than_comparisons() {
+        assert_eq!(tree("1 << 2 < 5"), "((1 << 2) < 5)");
+        assert_eq!(tree("n >> 1 > 0"), "((n >> 1) > 0)");
+        assert_eq!(tree("1 << 2 + 3"), "(1 << (2 + 3))");
+    }
+
+    #[test]
+    fn bitwise_operators_have_c_order() {
+        assert_eq!(tree("a | b & c"), "(a | (b & c))");
+        assert_eq!(tree("a ^ b & c"), "(a ^ (b & c))");
+        assert_eq!(tree("a | b ^ c"), "(a | (b ^ c))");
+        assert_eq!(tree("a & b << c"), "(a & (b << c))");
+        assert_eq!(tree("a | b | c"), "((a | b) | c)");
+    }
+
+    #[test]
+    fn bitwise_operators_bind_tighter_than_comparisons() {
+        assert_eq!(tree("flags & 4 == 0"), "((flags & 4) == 0)");
+        assert_eq!(tree("a | b < c"), "((a | b) < c)");
+        assert_eq!(tree("~a & b"), "((~a) & b)");
+    }
+
+    #[test]
+    fn assert_quotes_the_condition_as_written() {
+        let source = "assert -x >  0 and f( \"é\" ), \"msg\";";
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().unwrap();
+        let statements = Parser::new(tokens, source).parse().unwrap();
+        match &statements[0] {
+            Stmt::Assert(stmt) => assert_eq!(stmt.source, "-x >  0 and f( \"é\" )"),
+            _ => panic!("not an assert statement"),
+        }
+    }
+}
@@ -0,0 +1,771 @@
+use std::fmt;
+
+// A small regular expression engine. Patterns are compiled to a program
+// for a Pike VM, which runs all alternatives in lockstep: matching takes
+// time linear in the input and never recurses over it, so a bad pattern
+// cannot hang or overflow the stack on a long line.
+//
+// Supported syntax: literals, `.`, `[...]` classes with ranges and `^`
+// negation, `\d \w \s` and their negations, `^ $ \b \B`, groups `(...)`
+// and `(?:...)`, alternation `|`, and the quantifiers `* + ? {n} {n,}
+// {n,m}`, each with a lazy `?` form. Matches are leftmost-first, as in
+// Perl and JavaScript. `.` does not match a newline.
+pub struct Regex {
+    pub pattern: String,
+    program: Vec<Inst>,
+    groups: usize,
+}
+
+// A successful match. Slot `2 * n` and `2 * n + 1` hold the character
+// positions where group `n` starts and ends; group 0 is the whole match.
+pub struct Match {
+    slots: Vec<Option<usize>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RegexError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/{}/", self.pattern)
+    }
+}
+
+// Compiled patterns are values, so two are equal only if they are the same one.
+impl PartialEq for Regex {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Match {
+    pub fn group(&self, group: usize) -> Option<(usize, usize)> {
+        match (self.slots.get(2 * group)?, self.slots.get(2 * group + 1)?) {
+            (Some(start), Some(end)) => Some((*start, *end)),
+            _ => None,
+        }
+    }
+
+    pub fn start(&self) -> usize {
+        self.slots[0].unwrap()
+    }
+
+    pub fn end(&self) -> usize {
+        self.slots[1].unwrap()
+    }
+}
+
+const MAX_REPEAT: usize = 1000;
+const MAX_PROGRAM: usize = 10_000;
+// Groups nest through recursion in the parser and compiler.
+const MAX_NESTING: usize = 100;
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+        let mut parser = PatternParser {
+            chars: pattern.chars().collect(),
+            current: 0,
+            groups: 0,
+            depth: 0,
+        };
+        let node = parser.parse()?;
+
+        let mut compiler = Compiler {
+            program: Vec::new(),
+        };
+        compiler.push(Inst::Save(0));
+        compiler.emit(&node);
+        compiler.push(Inst::Save(1));
+        compiler.push(Inst::Match);
+        if compiler.program.len() > MAX_PROGRAM {
+            return Err(RegexError {
+                position: 0,
+                message: "Pattern is too large".to_string(),
+            });
+        }
+
+        Ok(Regex {
+            pattern: pattern.to_string(),
+            program: compiler.program,
+            groups: parser.groups,
+        })
+    }
+
+    // Number of capture groups, not counting the whole match.
+    pub fn groups(&self) -> usize {
+        self.groups
+    }
+
+    pub fn is_match(&self, text: &[char]) -> bool {
+        self.find_at(text, 0).is_some()
+    }
+
+    // The leftmost match starting at or after `start`.
+    pub fn find_at(&self, text: &[char], start: usize) -> Option<Match> {
+        let mut current: Vec<(usize, Vec<Option<usize>>)> = Vec::new();
+        let mut visited = vec![0; self.program.len()];
+        let mut matched = None;
+
+        for pos in start..=text.len() {
+            if matched.is_none() {
+                // a match starting here ranks below any already under way
+                let slots = vec![None; 2 * (self.groups + 1)];
+                self.add_thread(&mut current, &mut visited, 0, slots, text, pos);
+            }
+            if current.is_empty() {
+                if matched.is_some() {
+                    break;
+                }
+                continue;
+            }
+
+            let mut next = Vec::new();
+            for (pc, slots) in current.drain(..) {
+                let consumed = match &self.program[pc] {
+                    Inst::Match => {
+                        // threads after this one have lower priority
+                        matched = Some(Match { slots });
+                        break;
+                    }
+                    Inst::Char(c) => text.get(pos) == Some(c),
+                    Inst::Any => text.get(pos).is_some_and(|c| *c != '\n'),
+                    Inst::Class(class) => text.get(pos).is_some_and(|c| class.matches(*c)),
+                    _ => unreachable!("only consuming instructions are queued"),
+                };
+                if consumed {
+                    self.add_thread(&mut next, &mut visited, pc + 1, slots, text, pos + 1);
+                }
+            }
+            current = next;
+        }
+        matched
+    }
+
+    // Follows jumps, splits, saves and assertions from `pc`, queueing the
+    // instructions that consume input. `visited` marks the instructions
+    // already reached at `pos`, which also stops empty loops like `(a*)*`.
+    fn add_thread(
+        &self,
+        list: &mut Vec<(usize, Vec<Option<usize>>)>,
+        visited: &mut [usize],
+        pc: usize,
+        mut slots: Vec<Option<usize>>,
+        text: &[char],
+        pos: usize,
+    ) {
+        if visited[pc] == pos + 1 {
+            return;
+        }
+        visited[pc] = pos + 1;
+
+        match &self.program[pc] {
+            Inst::Jmp(target) => self.add_thread(list, visited, *target, slots, text, pos),
+            Inst::Split(first, second) => {
+                self.add_thread(list, visited, *first, slots.clone(), text, pos);
+                self.add_thread(list, visited, *second, slots, text, pos);
+            }
+            Inst::Save(slot) => {
+                slots[*slot] = Some(pos);
+                self.add_thread(list, visited, pc + 1, slots, text, pos);
+            }
+            Inst::Assert(assertion) => {
+                if assertion.holds(text, pos) {
+                    self.add_thread(list, visited, pc + 1, slots, text, pos);
+                }
+            }
+            _ => list.push((pc, slots)),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    // try the first target, then the second
+    Split(usize, usize),
+    Jmp(usize),
+    Save(usize),
+    Match,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Assertion {
+    Start,
+    End,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+impl Assertion {
+    fn holds(&self, text: &[char], pos: usize) -> bool {
+        let at_boundary = || {
+            let before = pos > 0 && is_word(text[pos - 1]);
+            let after = pos < text.len() && is_word(text[pos]);
+            before != after
+        };
+        match self {
+            Assertion::Start => pos == 0,
+            Assertion::End => pos == text.len(),
+            Assertion::WordBoundary => at_boundary(),
+            Assertion::NotWordBoundary => !at_boundary(),
+        }
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[derive(Debug, Clone)]
+struct Class {
+    negated: bool,
+    items: Vec<ClassItem>,
+}
+
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Range(char, char),
+    // `\d`, `\w` or `\s`, negated for `\D`, `\W` and `\S`
+    Perl(char, bool),
+}
+
+impl Class {
+    fn perl(kind: char, negated: bool) -> Class {
+        Class {
+            negated: false,
+            items: vec![ClassItem::Perl(kind, negated)],
+        }
+    }
+
+    fn matches(&self, c: char) -> bool {
+        let found = self.items.iter().any(|item| match item {
+            ClassItem::Range(low, high) => (*low..=*high).contains(&c),
+            ClassItem::Perl(kind, negated) => {
+                let found = match kind {
+                    'd' => c.is_ascii_digit(),
+                    'w' => is_word(c),
+                    _ => c.is_whitespace(),
+                };
+                found != *negated
+            }
+        });
+        found != self.negated
+    }
+}
+
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    // capturing groups carry their number
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+struct PatternParser {
+    chars: Vec<char>,
+    current: usize,
+    groups: usize,
+    // groups open at `current`
+    depth: usize,
+}
+
+impl PatternParser {
+    fn parse(&mut self) -> Result<Node, RegexError> {
+        let node = self.alternation()?;
+        if self.current < self.chars.len() {
+            return Err(self.error("Unmatched ')'"));
+        }
+        Ok(node)
+    }
+
+    fn alternation(&mut self) -> Result<Node, RegexError> {
+        let mut branches = vec![self.concatenation()?];
+        while self.eat('|') {
+            branches.push(self.concatenation()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.pop().unwrap()
+        } else {
+            Node::Alternate(branches)
+        })
+    }
+
+    fn concatenation(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            nodes.push(self.repetition()?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn repetition(&mut self) -> Result<Node, RegexError> {
+        let atom = self.atom()?;
+        let start = self.current;
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => match self.counts()? {
+                Some(counts) => counts,
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        if self.current == start {
+            self.current += 1;
+        }
+        if let Node::Assert(_) = atom {
+            return Err(self.error_at(start, "Nothing to repeat"));
+        }
+        let greedy = !self.eat('?');
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    // Parses `{n}`, `{n,}` or `{n,m}`. Anything else leaves the `{` to be
+    // read as a literal.
+    fn counts(&mut self) -> Result<Option<(usize, Option<usize>)>, RegexError> {
+        let start = self.current;
+        self.current += 1;
+        let min = self.count();
+        let max = if self.eat(',') { self.count() } else { min };
+        match (min, self.eat('}')) {
+            (Some(min), true) => {
+                if max.is_some_and(|max| max < min) {
+                    return Err(self.error_at(start, "Repetition range is out of order"));
+                }
+                if min.max(max.unwrap_or(0)) > MAX_REPEAT {
+                    return Err(self.error_at(start, "Repetition count is too large"));
+                }
+                Ok(Some((min, max)))
+            }
+            _ => {
+                self.current = start;
+                Ok(None)
+            }
+        }
+    }
+
+    fn count(&mut self) -> Option<usize> {
+        let start = self.current;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.current += 1;
+        }
+        let digits: String = self.chars[start..self.current].iter().collect();
+        // absurdly long counts saturate and are rejected by the caller
+        (!digits.is_empty()).then(|| digits.parse().unwrap_or(usize::MAX))
+    }
+
+    fn atom(&mut self) -> Result<Node, RegexError> {
+        let start = self.current;
+        let c = self.advance();
+        match c {
+            '(' => {
+                if self.depth == MAX_NESTING {
+                    return Err(self.error_at(start, "Pattern nests too deeply"));
+                }
+                let group = if self.eat('?') {
+                    if !self.eat(':') {
+                        return Err(self.error_at(start, "Unsupported group syntax"));
+                    }
+                    None
+                } else {
+                    self.groups += 1;
+                    Some(self.groups)
+                };
+                self.depth += 1;
+                let node = self.alternation()?;
+                self.depth -= 1;
+                if !self.eat(')') {
+                    return Err(self.error_at(start, "Missing ')'"));
+                }
+                Ok(Node::Group(Box::new(node), group))
+            }
+            '[' => self.class(start),
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Assert(Assertion::Start)),
+            '$' => Ok(Node::Assert(Assertion::End)),
+            '\\' => match self.escape(start)? {
+                Escaped::Char(c) => Ok(Node::Char(c)),
+                Escaped::Perl(kind, negated) => Ok(Node::Class(Class::perl(kind, negated))),
+                Escaped::Assert(assertion) => Ok(Node::Assert(assertion)),
+            },
+            '*' | '+' | '?' => Err(self.error_at(start, "Nothing to repeat")),
+            '{' => {
+                self.current = start;
+                if self.counts()?.is_some() {
+                    return Err(self.error_at(start, "Nothing to repeat"));
+                }
+                self.current = start + 1;
+                Ok(Node::Char('{'))
+            }
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    fn class(&mut self, start: usize) -> Result<Node, RegexError> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let item_start = self.current;
+            let low = match self.peek() {
+                None => return Err(self.error_at(start, "Missing ']'")),
+                Some(']') if !first => {
+                    self.current += 1;
+                    break;
+                }
+                Some('\\') => {
+                    self.current += 1;
+                    match self.escape(item_start)? {
+                        Escaped::Char(c) => c,
+                        Escaped::Perl(kind, negated) => {
+                            items.push(ClassItem::Perl(kind, negated));
+                            first = false;
+                            continue;
+                        }
+                        Escaped::Assert(_) => {
+                            return Err(self.error_at(item_start, "Assertion inside a class"))
+                        }
+                    }
+                }
+                Some(c) => {
+                    self.current += 1;
+                    c
+                }
+            };
+            first = false;
+
+            let is_range = self.peek() == Some('-')
+                && self.chars.get(self.current + 1).is_some_and(|c| *c != ']');
+            if !is_range {
+                items.push(ClassItem::Range(low, low));
+                continue;
+            }
+            self.current += 1;
+            let high_start = self.current;
+            let high = match self.advance() {
+                '\\' => match self.escape(high_start)? {
+                    Escaped::Char(c) => c,
+                    _ => return Err(self.error_at(high_start, "Invalid class range")),
+                },
+                c => c,
+            };
+            if high < low {
+                return Err(self.error_at(item_start, "Class range is out of order"));
+            }
+            items.push(ClassItem::Range(low, high));
+        }
+        Ok(Node::Class(Class { negated, items }))
+    }
+
+    // Reads the character after a backslash at `start`.
+    fn escape(&mut self, start: usize) -> Result<Escaped, RegexError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error_at(start, "Trailing backslash"));
+        };
+        self.current += 1;
+        match c {
+            'd' | 'w' | 's' => Ok(Escaped::Perl(c, false)),
+            'D' | 'W' | 'S' => Ok(Escaped::Perl(c.to_ascii_lowercase(), true)),
+            'b' => Ok(Escaped::Assert(Assertion::WordBoundary)),
+            'B' => Ok(Escaped::Assert(Assertion::NotWordBoundary)),
+            'n' => Ok(Escaped::Char('\n')),
+            'r' => Ok(Escaped::Char('\r')),
+            't' => Ok(Escaped::Char('\t')),
+            c if c.is_alphanumeric() => {
+                Err(self.error_at(start, &format!("Unknown escape '\\{}'", c)))
+            }
+            c => Ok(Escaped::Char(c)),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn advance(&mut self) -> char {
+        self.current += 1;
+        self.chars[self.current - 1]
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> RegexError {
+        self.error_at(self.current, message)
+    }
+
+    fn error_at(&self, position: usize, message: &str) -> RegexError {
+        RegexError {
+            position,
+            message: message.to_string(),
+        }
+    }
+}
+
+enum Escaped {
+    Char(char),
+    Perl(char, bool),
+    Assert(Assertion),
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn push(&mut self, inst: Inst) -> usize {
+        self.program.push(inst);
+        self.program.len() - 1
+    }
+
+    fn emit(&mut self, node: &Node) {
+        // stop growing absurd programs early; `Regex::new` reports them
+        if self.program.len() > MAX_PROGRAM {
+            return;
+        }
+        match node {
+            Node::Empty => {}
+            Node::Char(c) => {
+                self.push(Inst::Char(*c));
+            }
+            Node::Any => {
+                self.push(Inst::Any);
+            }
+            Node::Class(class) => {
+                self.push(Inst::Class(class.clone()));
+            }
+            Node::Assert(assertion) => {
+                self.push(Inst::Assert(*assertion));
+            }
+            Node::Group(node, None) => self.emit(node),
+            Node::Group(node, Some(group)) => {
+                self.push(Inst::Save(2 * group));
+                self.emit(node);
+                self.push(Inst::Save(2 * group + 1));
+            }
+            Node::Concat(nodes) => nodes.iter().for_each(|node| self.emit(node)),
+            Node::Alternate(branches) => {
+                let mut exits = Vec::new();
+                for (index, branch) in branches.iter().enumerate() {
+                    if index + 1 == branches.len() {
+                        self.emit(branch);
+                        break;
+                    }
+                    let split = self.push(Inst::Split(0, 0));
+                    self.emit(branch);
+                    exits.push(self.push(Inst::Jmp(0)));
+                    self.program[split] = Inst::Split(split + 1, self.program.len());
+                }
+                let end = self.program.len();
+                for exit in exits {
+                    self.program[exit] = Inst::Jmp(end);
+                }
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => {
+                for _ in 0..*min {
+                    self.emit(node);
+                }
+                match max {
+                    None => {
+                        let split = self.push(Inst::Split(0, 0));
+                        self.emit(node);
+                        self.push(Inst::Jmp(split));
+                        self.patch_split(split, *greedy);
+                    }
+                    Some(max) => {
+                        for _ in *min..*max {
+                            let split = self.push(Inst::Split(0, 0));
+                            self.emit(node);
+                            self.patch_split(split, *greedy);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Points an optional's split at its body and at what follows it,
+    // preferring the body when greedy.
+    fn patch_split(&mut self, split: usize, greedy: bool) {
+        let (body, after) = (split + 1, self.program.len());
+        self.program[split] = if greedy {
+            Inst::Split(body, after)
+        } else {
+            Inst::Split(after, body)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    // The text of each group in the first match, or `None` without one.
+    fn captures(pattern: &str, text: &str) -> Option<Vec<Option<String>>> {
+        let regex = Regex::new(pattern).unwrap();
+        let text = chars(text);
+        let found = regex.find_at(&text, 0)?;
+        Some(
+            (0..=regex.groups())
+                .map(|group| {
+                    found
+                        .group(group)
+                        .map(|(start, end)| text[start..end].iter().collect())
+                })
+                .collect(),
+        )
+    }
+
+    fn first_match(pattern: &str, text: &str) -> Option<String> {
+        captures(pattern, text)?.swap_remove(0)
+    }
+
+    #[test]
+    fn matches_literals_and_classes() {
+        assert_eq!(first_match("b+", "abbbc"), Some("bbb".to_string()));
+        assert_eq!(first_match("[a-c]+", "xxcabz"), Some("cab".to_string()));
+        assert_eq!(first_match("[^a-c ]+", "abc def"), Some("def".to_string()));
+        assert_eq!(first_match(r"\d{2,3}", "a1234"), Some("123".to_string()));
+        assert_eq!(
+            first_match(r"\w+@\w+\.com", "to: me@ex.com"),
+            Some("me@ex.com".to_string())
+        );
+        assert_eq!(first_match("a.c", "a\nc"), None);
+        assert_eq!(first_match("x{", "x{"), Some("x{".to_string()));
+    }
+
+    #[test]
+    fn anchors_and_boundaries() {
+        assert_eq!(first_match("^ab", "cab"), None);
+        assert_eq!(first_match("ab$", "abab"), Some("ab".to_string()));
+        assert_eq!(
+            first_match(r"\bcat\b", "concat cat"),
+            Some("cat".to_string())
+        );
+        assert!(Regex::new(r"\Bcat").unwrap().is_match(&chars("concat")));
+    }
+
+    #[test]
+    fn leftmost_first_alternation_and_laziness() {
+        assert_eq!(first_match("a|ab", "ab"), Some("a".to_string()));
+        assert_eq!(first_match("<.+>", "<a><b>"), Some("<a><b>".to_string()));
+        assert_eq!(first_match("<.+?>", "<a><b>"), Some("<a>".to_string()));
+        assert_eq!(first_match("x*", "yx"), Some("".to_string()));
+    }
+
+    #[test]
+    fn capture_groups() {
+        assert_eq!(
+            captures(r"(\d+)-(\d+)?(x)?", "tel 555-1234"),
+            Some(vec![
+                Some("555-1234".to_string()),
+                Some("555".to_string()),
+                Some("1234".to_string()),
+                None
+            ])
+        );
+        assert_eq!(
+            captures("(?:a(b))+", "abab"),
+            Some(vec![Some("abab".to_string()), Some("b".to_string())])
+        );
+    }
+
+    #[test]
+    fn pathological_patterns_stay_fast() {
+        let text = "a".repeat(5000);
+        assert!(!Regex::new("(a*)*b").unwrap().is_match(&chars(&text)));
+        assert!(Regex::new("(a|aa)+$").unwrap().is_match(&chars(&text)));
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let nested = |depth| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Regex::new(&nested(MAX_NESTING)).is_ok());
+        assert_eq!(
+            Regex::new(&nested(MAX_NESTING + 1))
+                .err()
+                .map(|e| e.to_string()),
+            Some(format!(
+                "Pattern nests too deeply at position {}",
+                MAX_NESTING
+            ))
+        );
+        assert!(Regex::new(&nested(10_000)).is_err());
+        assert!(Regex::new(&"(?:".repeat(10_000)).is_err());
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let error = |pattern| Regex::new(pattern).err().map(|e| e.to_string());
+        assert_eq!(error("a(b"), Some("Missing ')' at position 1".to_string()));
+        assert_eq!(error("a)"), Some("Unmatched ')' at position 1".to_string()));
+        assert_eq!(
+            error("*a"),
+            Some("Nothing to repeat at position 0".to_string())
+        );
+        assert_eq!(
+            error("[b-a]"),
+            Some("Class range is out of order at position 1".to_string())
+        );
+        assert_eq!(
+            error(r"\q"),
+            Some("Unknown escape '\\q' at position 0".to_string())
+        );
+        assert_eq!(
+            error("a{3,2}"),
+            Some("Repetition range is out of order at position 1".to_string())
+        );
+        assert!(error("[abc").is_some());
+        assert!(error("a**").is_some());
+    }
+}
@@ -3,22 +3,31 @@ use crate::literal::Literal;
 use crate::token::*;
 use crate::token_type::*;
 
+// `start` and `current` index chars, not bytes, so that multi-byte UTF-8
+// characters never split a lexeme or cause a slice on a non-char boundary.
 pub struct Scanner {
-    source: String,
+    source: Vec<char>,
     tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: usize,
+    // char index where the current line begins, for column numbers
+    line_start: usize,
+    start_line: usize,
+    start_column: usize,
 }
 
 impl Scanner {
     pub fn new(source: String) -> Scanner {
         Scanner {
-            source,
+            source: source.chars().collect(),
             tokens: Vec::new(),
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            start_line: 1,
+            start_column: 1,
         }
     }
 
@@ -26,6 +35,8 @@ impl Scanner {
         let mut had_error: Option<LoxResult> = None;
         while !self.is_at_end() {
             self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.column();
             match self.scan_token() {
                 Ok(_) => {}
                 Err(e) => {
@@ -34,7 +45,7 @@ impl Scanner {
             }
         }
 
-        self.tokens.push(Token::eof(self.line));
+        self.tokens.push(Token::eof(self.line, self.column()));
 
         if let Some(e) = had_error {
             Err(e)
@@ -47,6 +58,16 @@ impl Scanner {
         self.current >= self.source.len()
     }
 
+    // 1-based column of `current`, counted in characters
+    fn column(&self) -> usize {
+        self.current - self.line_start + 1
+    }
+
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.line_start = self.current;
+    }
+
     fn scan_token(&mut self) -> Result<(), LoxResult> {
         let c = self.advance();
         match c {
@@ -54,12 +75,17 @@ impl Scanner {
             ')' => self.add_token(TokenType::RightParen),
             '{' => self.add_token(TokenType::LeftBrace),
             '}' => self.add_token(TokenType::RightBrace),
+            ':' => self.add_token(TokenType::Colon),
             ',' => self.add_token(TokenType::Comma),
             '.' => self.add_token(TokenType::Dot),
             '-' => self.add_token(TokenType::Minus),
             '+' => self.add_token(TokenType::Plus),
             ';' => self.add_token(TokenType::SemiColon),
             '*' => self.add_token(TokenType::Star),
+            '&' => self.add_token(TokenType::Ampersand),
+            '|' => self.add_token(TokenType::Pipe),
+            '^' => self.add_token(TokenType::Caret),
+            '~' => self.add_token(TokenType::Tilde),
             '!' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::BangEqual);
@@ -77,6 +103,8 @@ impl Scanner {
             '<' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::LessEqual);
+                } else if self.match_char('<') {
+                    self.add_token(TokenType::LessLess);
                 } else {
                     self.add_token(TokenType::Less);
                 }
@@ -84,10 +112,23 @@ impl Scanner {
             '>' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::GreaterEqual);
+                } else if self.match_char('>') {
+                    self.add_token(TokenType::GreaterGreater);
                 } else {
                     self.add_token(TokenType::Greater);
                 }
             }
+            '?' => {
+                if self.match_char('.') {
+                    self.add_token(TokenType::QuestionDot);
+                } else {
+                    return Err(LoxResult::error(
+                        self.start_line,
+                        self.start_column,
+                        "Unexpected character.",
+                    ));
+                }
+            }
             '/' => {
                 if self.match_char('/') {
                     // this means comment
@@ -100,7 +141,7 @@ impl Scanner {
             }
             ' ' | '\r' | '\t' => {}
             '\n' => {
-                self.line += 1;
+                self.new_line();
             }
             '"' => {
                 self.scan_string();
@@ -111,7 +152,11 @@ impl Scanner {
                 } else if self.is_alpha(c) {
                     self.identifier();
                 } else {
-                    return Err(LoxResult::error(self.line, "Unexpected character."));
+                    return Err(LoxResult::error(
+                        self.start_line,
+                        self.start_column,
+                        "Unexpected character.",
+                    ));
                 }
             }
         }
@@ -122,7 +167,7 @@ impl Scanner {
         while self.is_alpha_numeric(self.peek()) {
             self.advance();
         }
-        let text = self.source[self.start..self.current].to_string();
+        let text = self.lexeme();
         if let Some(ttype) = Scanner::keyword(text.as_str()) {
             self.add_token(ttype);
         } else {
@@ -143,31 +188,30 @@ impl Scanner {
                 self.advance();
             }
         }
-        let number = self.source[self.start..self.current]
-            .parse::<f64>()
-            .unwrap();
+        let number = self.lexeme().parse::<f64>().unwrap();
         self.add_token_object(TokenType::Number, Some(Literal::Number(number)));
         // self.add_token(TokenType::Number);
     }
 
     fn scan_string(&mut self) {
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
-            }
             self.advance();
+            if self.previous() == '\n' {
+                self.new_line();
+            }
         }
 
         if self.is_at_end() {
-            LoxResult::error(self.line, "Unterminated string.");
+            LoxResult::error(self.start_line, self.start_column, "Unterminated string.");
             return;
         }
 
         // the closing "
         self.advance();
 
-        // value: String = self.source.chars().skip(self.start + 1).take(self.current - self.start + 2).collect();
-        let value: String = self.source[self.start + 1..self.current - 1].to_string();
+        let value: String = self.source[self.start + 1..self.current - 1]
+            .iter()
+            .collect();
         self.add_token_object(TokenType::String, Some(Literal::String(value)));
     }
 
@@ -175,7 +219,7 @@ impl Scanner {
         if self.is_at_end() {
             return false;
         }
-        if self.source.chars().nth(self.current).unwrap() != expected {
+        if self.source[self.current] != expected {
             return false;
         }
         self.current += 1;
@@ -186,30 +230,35 @@ impl Scanner {
         if self.is_at_end() {
             return '\0';
         }
-        self.source.chars().nth(self.current).unwrap()
+        self.source[self.current]
+    }
+
+    fn previous(&self) -> char {
+        self.source[self.current - 1]
     }
 
     fn peek_next(&self) -> char {
         if self.current + 1 >= self.source.len() {
             return '\0';
         }
-        self.source.chars().nth(self.current + 1).unwrap()
+        self.source[self.current + 1]
     }
 
+    // identifiers follow the Unicode XID rules, plus a leading '_'
     fn is_alpha(&self, c: char) -> bool {
-        (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || c == '_'
+        c == '_' || unicode_ident::is_xid_start(c)
     }
 
     fn is_dight(&self, c: char) -> bool {
-        return c >= '0' && c <= '9';
+        c.is_ascii_digit()
     }
 
     fn is_alpha_numeric(&self, c: char) -> bool {
-        self.is_alpha(c) || self.is_dight(c)
+        unicode_ident::is_xid_continue(c)
     }
 
     fn advance(&mut self) -> char {
-        let res = self.source.chars().nth(self.current).unwrap();
+        let res = self.source[self.current];
         self.current += 1;
         res
     }
@@ -218,21 +267,28 @@ impl Scanner {
         self.add_token_object(token_type, None);
     }
     fn add_token_object(&mut self, token_type: TokenType, literal: Option<Literal>) {
-        let lexeme: String = self
-            .source
-            .chars()
-            .skip(self.start)
-            .take(self.current - self.start)
-            .collect();
-        self.tokens
-            .push(Token::new(token_type, lexeme, literal, self.line));
+        self.tokens.push(Token {
+            token_type,
+            lexeme: self.lexeme(),
+            literal,
+            line: self.start_line,
+            column: self.start_column,
+            offset: self.start,
+        });
+    }
+
+    fn lexeme(&self) -> String {
+        self.source[self.start..self.current].iter().collect()
     }
 
     fn keyword(check: &str) -> Option<TokenType> {
         match check {
             "and" => Some(TokenType::And),
+            "assert" => Some(TokenType::Assert),
             "class" => Some(TokenType::Class),
+            "do" => Some(TokenType::Do),
             "else" => Some(TokenType::Else),
+            "enum" => Some(TokenType::Enum),
             "false" => Some(TokenType::False),
             "for" => Some(TokenType::For),
             "fun" => Some(TokenType::Fun),
@@ -247,7 +303,63 @@ impl Scanner {
             "var" => Some(TokenType::Var),
             "while" => Some(TokenType::While),
             "break" => Some(TokenType::Break),
+            "continue" => Some(TokenType::Continue),
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> Vec<Token> {
+        let mut scanner = Scanner::new(source.to_string());
+        scanner
+            .scan_tokens()
+            .unwrap()
+            .iter()
+            .map(|t| t.dup())
+            .collect()
+    }
+
+    #[test]
+    fn scans_non_ascii_strings() {
+        let tokens = scan("print \"héllo ☕\";");
+        assert_eq!(
+            tokens[1].literal,
+            Some(Literal::String("héllo ☕".to_string()))
+        );
+        assert!(tokens[2].is(TokenType::SemiColon));
+    }
+
+    #[test]
+    fn scans_unicode_identifiers() {
+        let tokens = scan("var grüße = 名前;");
+        assert!(tokens[1].is(TokenType::Identifier));
+        assert_eq!(tokens[1].as_string(), "grüße");
+        assert!(tokens[3].is(TokenType::Identifier));
+        assert_eq!(tokens[3].as_string(), "名前");
+    }
+
+    #[test]
+    fn skips_non_ascii_comments() {
+        let tokens = scan("// ☕ café\n1;");
+        assert!(tokens[0].is(TokenType::Number));
+        assert_eq!(tokens[0].line, 2);
+    }
+
+    #[test]
+    fn columns_count_characters() {
+        let tokens = scan("\"ü☕\" + x;\n  y");
+        assert_eq!(tokens[1].column, 6);
+        assert_eq!(tokens[2].column, 8);
+        assert_eq!((tokens[4].line, tokens[4].column), (2, 3));
+    }
+
+    #[test]
+    fn rejects_non_identifier_symbols() {
+        let mut scanner = Scanner::new("var x = ☕;".to_string());
+        assert!(scanner.scan_tokens().is_err());
+    }
+}
@@ -3,8 +3,12 @@ use crate::expr::*;
 use crate::token::*;
 
 pub enum Stmt {
+    Assert(AssertStmt),
     Block(BlockStmt),
     Break(BreakStmt),
+    Continue(ContinueStmt),
+    Do(DoStmt),
+    Enum(EnumStmt),
     Expression(ExpressionStmt),
     If(IfStmt),
     Print(PrintStmt),
@@ -15,8 +19,12 @@ pub enum Stmt {
 impl Stmt {
     pub fn accept<T>(&self, stmt_visitor: &dyn StmtVisitor<T>) -> Result<T, LoxResult> {
         match self {
+            Stmt::Assert(v) => v.accept(stmt_visitor),
             Stmt::Block(v) => v.accept(stmt_visitor),
             Stmt::Break(v) => v.accept(stmt_visitor),
+            Stmt::Continue(v) => v.accept(stmt_visitor),
+            Stmt::Do(v) => v.accept(stmt_visitor),
+            Stmt::Enum(v) => v.accept(stmt_visitor),
             Stmt::Expression(v) => v.accept(stmt_visitor),
             Stmt::If(v) => v.accept(stmt_visitor),
             Stmt::Print(v) => v.accept(stmt_visitor),
@@ -26,12 +34,36 @@ impl Stmt {
     }
 }
 
+pub struct AssertStmt {
+    pub keyword: Token,
+    pub condition: Expr,
+    pub message: Option<Expr>,
+    pub source: String,
+}
+
 pub struct BlockStmt {
     pub statements: Vec<Stmt>,
 }
 
 pub struct BreakStmt {
     pub token: Token,
+    pub label: Option<Token>,
+}
+
+pub struct ContinueStmt {
+    pub token: Token,
+    pub label: Option<Token>,
+}
+
+pub struct DoStmt {
+    pub body: Box<Stmt>,
+    pub condition: Expr,
+    pub label: Option<Token>,
+}
+
+pub struct EnumStmt {
+    pub name: Token,
+    pub members: Vec<Token>,
 }
 
 pub struct ExpressionStmt {
@@ -50,17 +82,24 @@ pub struct PrintStmt {
 
 pub struct VarStmt {
     pub name: Token,
+    pub type_annotation: Option<Token>,
     pub initializer: Option<Expr>,
 }
 
 pub struct WhileStmt {
     pub condition: Expr,
     pub body: Box<Stmt>,
+    pub increment: Option<Expr>,
+    pub label: Option<Token>,
 }
 
 pub trait StmtVisitor<T> {
+    fn visit_assert_stmt(&self, expr: &AssertStmt) -> Result<T, LoxResult>;
     fn visit_block_stmt(&self, expr: &BlockStmt) -> Result<T, LoxResult>;
     fn visit_break_stmt(&self, expr: &BreakStmt) -> Result<T, LoxResult>;
+    fn visit_continue_stmt(&self, expr: &ContinueStmt) -> Result<T, LoxResult>;
+    fn visit_do_stmt(&self, expr: &DoStmt) -> Result<T, LoxResult>;
+    fn visit_enum_stmt(&self, expr: &EnumStmt) -> Result<T, LoxResult>;
     fn visit_expression_stmt(&self, expr: &ExpressionStmt) -> Result<T, LoxResult>;
     fn visit_if_stmt(&self, expr: &IfStmt) -> Result<T, LoxResult>;
     fn visit_print_stmt(&self, expr: &PrintStmt) -> Result<T, LoxResult>;
@@ -68,6 +107,12 @@ pub trait StmtVisitor<T> {
     fn visit_while_stmt(&self, expr: &WhileStmt) -> Result<T, LoxResult>;
 }
 
+impl AssertStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxResult> {
+        visitor.visit_assert_stmt(self)
+    }
+}
+
 impl BlockStmt {
     pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxResult> {
         visitor.visit_block_stmt(self)
@@ -80,6 +125,24 @@ impl BreakStmt {
     }
 }
 
+impl ContinueStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxResult> {
+        visitor.visit_continue_stmt(self)
+    }
+}
+
+impl DoStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxResult> {
+        visitor.visit_do_stmt(self)
+    }
+}
+
+impl EnumStmt {
+    pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxResult> {
+        visitor.visit_enum_stmt(self)
+    }
+}
+
 impl ExpressionStmt {
     pub fn accept<T>(&self, visitor: &dyn StmtVisitor<T>) -> Result<T, LoxResult> {
         visitor.visit_expression_stmt(self)
@@ -9,6 +9,10 @@ pub struct Token {
     pub lexeme: String,
     pub literal: Option<Literal>,
     pub line: usize,
+    pub column: usize,
+    // char offset of the lexeme in the source; 0 for tokens not scanned
+    // from source
+    pub offset: usize,
 }
 
 impl Token {
@@ -17,12 +21,15 @@ impl Token {
         lexeme: String,
         literal: Option<Literal>,
         line: usize,
+        column: usize,
     ) -> Token {
         Token {
             token_type,
             lexeme,
             literal,
             line,
+            column,
+            offset: 0,
         }
     }
 
@@ -44,15 +51,19 @@ impl Token {
             lexeme: self.lexeme.to_string(),
             literal: self.literal.clone(),
             line: self.line,
+            column: self.column,
+            offset: self.offset,
         }
     }
 
-    pub fn eof(line: usize) -> Token {
+    pub fn eof(line: usize, column: usize) -> Token {
         Token {
             token_type: TokenType::Eof,
             lexeme: "".to_string(),
             literal: None,
             line,
+            column,
+            offset: 0,
         }
     }
 }
@@ -1,21 +1,23 @@
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum TokenType {
     // Single-character tokens.
-    Break,
+    Break, Continue,
     LeftParen, RightParen, LeftBrace, RightBrace,
-    Comma, Dot, Minus, Plus, SemiColon, Slash, Star,
+    Colon, Comma, Dot, Minus, Plus, SemiColon, Slash, Star,
+    Ampersand, Pipe, Caret, Tilde,
 
     // One or two character tokens.
     Bang, BangEqual,
     Equal, EqualEqual,
-    Greater, GreaterEqual,
-    Less, LessEqual,
+    Greater, GreaterEqual, GreaterGreater,
+    Less, LessEqual, LessLess,
+    QuestionDot,
 
     // Literals.
     Identifier, String, Number,
 
     // Keywords.
-    And, Class, Else, False, Fun, For, If, Nil, Or,
+    And, Assert, Class, Do, Else, Enum, False, Fun, For, If, Nil, Or,
     Print, Return, Super, This, True, Var, While,
 
     Eof,
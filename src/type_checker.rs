@@ -0,0 +1,458 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::callable::*;
+use crate::error::*;
+use crate::expr::*;
+use crate::interpreter::*;
+use crate::literal::*;
+use crate::stmt::*;
+use crate::token::*;
+use crate::token_type::*;
+
+// Static types for gradual typing. `Any` is the type of everything that
+// is not annotated, and is compatible with every other type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type {
+    Any,
+    Nil,
+    Bool,
+    Number,
+    String,
+    Function,
+}
+
+impl Type {
+    fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "Any" => Some(Type::Any),
+            "Nil" => Some(Type::Nil),
+            "Bool" => Some(Type::Bool),
+            "Number" => Some(Type::Number),
+            "String" => Some(Type::String),
+            "Function" => Some(Type::Function),
+            _ => None,
+        }
+    }
+
+    fn of(literal: &Literal) -> Type {
+        match literal {
+            Literal::Nil => Type::Nil,
+            Literal::Bool(_) => Type::Bool,
+            Literal::Number(_) => Type::Number,
+            Literal::String(_) => Type::String,
+            Literal::Func(_) => Type::Function,
+            _ => Type::Any,
+        }
+    }
+
+    fn accepts(&self, other: Type) -> bool {
+        *self == Type::Any || other == Type::Any || *self == other
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// Checks annotated code before it runs. Type errors are reported as they
+// are found and checking carries on, so one pass shows all of them.
+pub struct TypeChecker<'a> {
+    interpreter: &'a Interpreter,
+    scopes: RefCell<Vec<HashMap<String, Type>>>,
+    had_error: RefCell<bool>,
+}
+
+impl StmtVisitor<()> for TypeChecker<'_> {
+    fn visit_assert_stmt(&self, stmt: &AssertStmt) -> Result<(), LoxResult> {
+        self.check_expr(&stmt.condition)?;
+        if let Some(message) = &stmt.message {
+            self.check_expr(message)?;
+        }
+        Ok(())
+    }
+
+    fn visit_block_stmt(&self, stmt: &BlockStmt) -> Result<(), LoxResult> {
+        self.scopes.borrow_mut().push(HashMap::new());
+        let result = self.check_stmts(&stmt.statements);
+        self.scopes.borrow_mut().pop();
+        result
+    }
+
+    fn visit_break_stmt(&self, _stmt: &BreakStmt) -> Result<(), LoxResult> {
+        Ok(())
+    }
+
+    fn visit_continue_stmt(&self, _stmt: &ContinueStmt) -> Result<(), LoxResult> {
+        Ok(())
+    }
+
+    fn visit_do_stmt(&self, stmt: &DoStmt) -> Result<(), LoxResult> {
+        self.check_stmt(&stmt.body)?;
+        self.check_expr(&stmt.condition)?;
+        Ok(())
+    }
+
+    fn visit_enum_stmt(&self, stmt: &EnumStmt) -> Result<(), LoxResult> {
+        self.declare(&stmt.name, Type::Any);
+        Ok(())
+    }
+
+    fn visit_expression_stmt(&self, stmt: &ExpressionStmt) -> Result<(), LoxResult> {
+        self.check_expr(&stmt.expression)?;
+        Ok(())
+    }
+
+    fn visit_if_stmt(&self, stmt: &IfStmt) -> Result<(), LoxResult> {
+        self.check_expr(&stmt.condition)?;
+        self.check_stmt(&stmt.then_branch)?;
+        if let Some(else_branch) = &stmt.else_branch {
+            self.check_stmt(else_branch)?;
+        }
+        Ok(())
+    }
+
+    fn visit_print_stmt(&self, stmt: &PrintStmt) -> Result<(), LoxResult> {
+        self.check_expr(&stmt.expression)?;
+        Ok(())
+    }
+
+    fn visit_var_stmt(&self, stmt: &VarStmt) -> Result<(), LoxResult> {
+        // `var x: Number;` is fine; only an initializer is checked
+        let value = match &stmt.initializer {
+            Some(initializer) => self.check_expr(initializer)?,
+            None => Type::Any,
+        };
+
+        let declared = match &stmt.type_annotation {
+            Some(annotation) => match Type::from_name(annotation.as_string()) {
+                Some(declared) => {
+                    if !declared.accepts(value) {
+                        self.error(
+                            &stmt.name,
+                            &format!(
+                                "Cannot initialize '{}' of type {} with {}.",
+                                stmt.name.as_string(),
+                                declared,
+                                value
+                            ),
+                        );
+                    }
+                    declared
+                }
+                None => {
+                    self.error(
+                        annotation,
+                        &format!("Unknown type '{}'.", annotation.as_string()),
+                    );
+                    Type::Any
+                }
+            },
+            None => Type::Any,
+        };
+
+        self.declare(&stmt.name, declared);
+        Ok(())
+    }
+
+    fn visit_while_stmt(&self, stmt: &WhileStmt) -> Result<(), LoxResult> {
+        self.check_expr(&stmt.condition)?;
+        self.check_stmt(&stmt.body)?;
+        if let Some(increment) = &stmt.increment {
+            self.check_expr(increment)?;
+        }
+        Ok(())
+    }
+}
+
+impl ExprVisitor<Type> for TypeChecker<'_> {
+    fn visit_assign_expr(&self, expr: &AssignExpr) -> Result<Type, LoxResult> {
+        let value = self.check_expr(&expr.value)?;
+        let declared = self.lookup(&expr.name);
+        if !declared.accepts(value) {
+            self.error(
+                &expr.name,
+                &format!(
+                    "Cannot assign {} to '{}' of type {}.",
+                    value,
+                    expr.name.as_string(),
+                    declared
+                ),
+            );
+        }
+        Ok(value)
+    }
+
+    fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<Type, LoxResult> {
+        let left = self.check_expr(&expr.left)?;
+        let right = self.check_expr(&expr.right)?;
+
+        match expr.operator.token_type() {
+            TokenType::EqualEqual | TokenType::BangEqual => Ok(Type::Bool),
+            TokenType::Plus => match (left, right) {
+                (Type::Number, Type::Number) => Ok(Type::Number),
+                (Type::String, Type::String | Type::Number | Type::Any)
+                | (Type::Number | Type::Any, Type::String) => Ok(Type::String),
+                (Type::Number | Type::Any, Type::Number | Type::Any) => Ok(Type::Any),
+                _ => {
+                    self.error(
+                        &expr.operator,
+                        &format!(
+                            "Operands of '+' must be numbers or strings, found {} and {}.",
+                            left, right
+                        ),
+                    );
+                    Ok(Type::Any)
+                }
+            },
+            TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual => {
+                self.expect_numbers(&expr.operator, left, right);
+                Ok(Type::Bool)
+            }
+            _ => {
+                self.expect_numbers(&expr.operator, left, right);
+                Ok(Type::Number)
+            }
+        }
+    }
+
+    fn visit_call_expr(&self, expr: &CallExpr) -> Result<Type, LoxResult> {
+        let callee = self.check_expr(&expr.callee)?;
+        for argument in &expr.arguments {
+            self.check_expr(argument)?;
+        }
+        let skipped = expr.optional && callee == Type::Nil;
+        if !Type::Function.accepts(callee) && !skipped {
+            self.error(
+                &expr.paren,
+                &format!("Can only call functions and classes, found {}.", callee),
+            );
+        } else if let Some(function) = self.known_function(&expr.callee) {
+            self.expect_arguments(&expr.paren, &function, expr.arguments.len());
+        }
+        // argument types are not checked: no callable declares parameter
+        // types, and natives check their own arguments when called
+        Ok(Type::Any)
+    }
+
+    fn visit_get_expr(&self, expr: &GetExpr) -> Result<Type, LoxResult> {
+        self.check_expr(&expr.object)?;
+        Ok(Type::Any)
+    }
+
+    fn visit_grouping_expr(&self, expr: &GroupingExpr) -> Result<Type, LoxResult> {
+        self.check_expr(&expr.expression)
+    }
+
+    fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<Type, LoxResult> {
+        Ok(expr.value.as_ref().map_or(Type::Nil, Type::of))
+    }
+
+    fn visit_logical_expr(&self, expr: &LogicalExpr) -> Result<Type, LoxResult> {
+        let left = self.check_expr(&expr.left)?;
+        let right = self.check_expr(&expr.right)?;
+        Ok(if left == right { left } else { Type::Any })
+    }
+
+    fn visit_unary_expr(&self, expr: &UnaryExpr) -> Result<Type, LoxResult> {
+        let right = self.check_expr(&expr.right)?;
+        match expr.operator.token_type() {
+            TokenType::Bang => Ok(Type::Bool),
+            _ => {
+                if !Type::Number.accepts(right) {
+                    self.error(
+                        &expr.operator,
+                        &format!(
+                            "Operand of '{}' must be a number, found {}.",
+                            expr.operator.as_string(),
+                            right
+                        ),
+                    );
+                }
+                Ok(Type::Number)
+            }
+        }
+    }
+
+    fn visit_variable_expr(&self, expr: &VariableExpr) -> Result<Type, LoxResult> {
+        Ok(self.lookup(&expr.name))
+    }
+}
+
+impl<'a> TypeChecker<'a> {
+    // Undeclared names are looked up in `interpreter`'s globals, so calls
+    // to natives are checked against their arity.
+    pub fn new(interpreter: &'a Interpreter) -> TypeChecker<'a> {
+        TypeChecker {
+            interpreter,
+            scopes: RefCell::new(vec![HashMap::new()]),
+            had_error: RefCell::new(false),
+        }
+    }
+
+    pub fn check(&self, statements: &[Stmt]) -> bool {
+        *self.had_error.borrow_mut() = false;
+        self.check_stmts(statements).is_ok() && !*self.had_error.borrow()
+    }
+
+    fn check_stmts(&self, statements: &[Stmt]) -> Result<(), LoxResult> {
+        statements
+            .iter()
+            .try_for_each(|statement| self.check_stmt(statement))
+    }
+
+    fn check_stmt(&self, stmt: &Stmt) -> Result<(), LoxResult> {
+        stmt.accept(self)
+    }
+
+    fn check_expr(&self, expr: &Expr) -> Result<Type, LoxResult> {
+        expr.accept(self)
+    }
+
+    fn declare(&self, name: &Token, ttype: Type) {
+        self.scopes
+            .borrow_mut()
+            .last_mut()
+            .unwrap()
+            .insert(name.as_string().to_string(), ttype);
+    }
+
+    // Unknown names are left for the interpreter to report at runtime.
+    fn lookup(&self, name: &Token) -> Type {
+        self.lookup_declared(name).unwrap_or(Type::Any)
+    }
+
+    fn lookup_declared(&self, name: &Token) -> Option<Type> {
+        self.scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name.as_string()).copied())
+    }
+
+    // The function `callee` names, if it is a global the script has not
+    // declared over.
+    fn known_function(&self, callee: &Expr) -> Option<Callable> {
+        let Expr::Variable(variable) = callee else {
+            return None;
+        };
+        if self.lookup_declared(&variable.name).is_some() {
+            return None;
+        }
+        match self.interpreter.global(variable.name.as_string()) {
+            Some(Literal::Func(function)) => Some(function),
+            _ => None,
+        }
+    }
+
+    fn expect_arguments(&self, paren: &Token, function: &Callable, count: usize) {
+        let arity = function.arity();
+        if function.is_variadic() && count < arity {
+            self.error(
+                paren,
+                &format!("Expected at least {} arguments but got {}.", arity, count),
+            );
+        } else if !function.is_variadic() && count != arity {
+            self.error(
+                paren,
+                &format!("Expected {} arguments but got {}.", arity, count),
+            );
+        }
+    }
+
+    fn expect_numbers(&self, operator: &Token, left: Type, right: Type) {
+        if !Type::Number.accepts(left) || !Type::Number.accepts(right) {
+            self.error(
+                operator,
+                &format!(
+                    "Operands of '{}' must be numbers, found {} and {}.",
+                    operator.as_string(),
+                    left,
+                    right
+                ),
+            );
+        }
+    }
+
+    fn error(&self, token: &Token, message: &str) {
+        *self.had_error.borrow_mut() = true;
+        LoxResult::type_error(token, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::*;
+    use crate::scanner::*;
+
+    fn check(source: &str) -> bool {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens, source);
+        let statements = parser.parse().unwrap();
+        TypeChecker::new(&Interpreter::new()).check(&statements)
+    }
+
+    #[test]
+    fn unannotated_code_is_dynamic() {
+        assert!(check("var a = \"a\"; var b = 1; print a - b;"));
+    }
+
+    #[test]
+    fn reports_mismatched_literal_operands() {
+        assert!(!check("print \"a\" - 1;"));
+        assert!(!check("print true < 2;"));
+        assert!(check("print \"a\" + 1;"));
+    }
+
+    #[test]
+    fn checks_annotated_variables() {
+        assert!(check("var x: Number = 1; print x * 2;"));
+        assert!(!check("var x: Number = \"one\";"));
+        assert!(!check("var x: String = \"one\"; print x - 1;"));
+        assert!(!check("var x: Number = 1; x = true;"));
+    }
+
+    #[test]
+    fn annotated_variables_need_no_initializer() {
+        assert!(check("var x: Number; x = 1; print x * 2;"));
+        assert!(!check("var x: Number; x = \"one\";"));
+    }
+
+    #[test]
+    fn reports_unknown_types() {
+        assert!(!check("var x: Numbr = 1;"));
+    }
+
+    #[test]
+    fn reports_calling_non_callables() {
+        assert!(!check("var x: Number = 1; x();"));
+        assert!(!check("\"text\"();"));
+        assert!(check("var f = clock; f();"));
+    }
+
+    #[test]
+    fn counts_arguments_to_natives() {
+        assert!(check("print sqrt(4);"));
+        assert!(!check("print sqrt(4, 2);"));
+        assert!(!check("print clock(1);"));
+        assert!(check("print max(1, 2, 3);"));
+        assert!(!check("print max();"));
+        // a declared name is no longer the native
+        assert!(check("var sqrt = clock; print sqrt(4, 2);"));
+    }
+
+    #[test]
+    fn block_scopes_shadow_annotations() {
+        assert!(check(
+            "var x: Number = 1; { var x: String = \"s\"; print x + \"!\"; } print x - 1;"
+        ));
+    }
+}